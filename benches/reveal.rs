@@ -0,0 +1,46 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use dinomite_cmd::components::lib::dinomite::{Dinomite, Position};
+
+fn first_click(c: &mut Criterion) {
+    // the dinos are hidden on the first click
+    c.bench_function("first click 1000x1000, 150000 dinos", |b| {
+        b.iter_batched(
+            || {
+                let mut dinomite = Dinomite::new(1000, 1000, 150_000);
+                dinomite.set_safe_start(true);
+                dinomite
+            },
+            |mut dinomite| dinomite.check_position(&Position(500, 500)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn reveal_empty(c: &mut Criterion) {
+    // a single click opens the whole board
+    c.bench_function("reveal 1000x1000, no dinos", |b| {
+        b.iter_batched(
+            || Dinomite::new(1000, 1000, 0),
+            |mut dinomite| dinomite.check_position(&Position(500, 500)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn reveal_sparse(c: &mut Criterion) {
+    // one dino in a corner, so the opening is bounded by a few numbered fields
+    c.bench_function("reveal 1000x1000, one dino", |b| {
+        b.iter_batched(
+            || {
+                let mut dinomite = Dinomite::new(1000, 1000, 0);
+                dinomite.set_dino(&Position(0, 0));
+                dinomite
+            },
+            |mut dinomite| dinomite.check_position(&Position(999, 999)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, first_click, reveal_empty, reveal_sparse);
+criterion_main!(benches);
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
+use crate::components::game::GameResult;
+
 #[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
 pub enum Action {
     Tick,
@@ -10,14 +12,96 @@ pub enum Action {
     Resume,
     Quit,
     ClearScreen,
+    // shown for a few seconds and kept in the log
     Error(String),
+    Warning(String),
+    // keys typed so far of an incomplete sequence, empty once it is done
+    PendingKeys(String),
+    // a short message for the status line, and one that stays until replaced
+    Status(String),
+    StatusError(String),
+    // the config files changed on disk
+    ReloadConfig,
     Help,
     Flag,
+    Unflag,
     MoveRight,
     MoveLeft,
     MoveUp,
     MoveDown,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    MoveTo(usize, usize),
+    MoveRowStart,
+    MoveRowEnd,
+    MoveTop,
+    MoveBottom,
+    // with the column or row counted from 0
+    MoveToColumn(usize),
+    MoveToRow(usize),
+    // the next covered field in reading order, wrapping around at the end
+    MoveNextCovered,
+    MovePreviousCovered,
+    // the next uncovered number that still has covered fields next to it
+    MoveNextFrontier,
+    MovePreviousFrontier,
+    // ask for a field to move the cursor to
+    GoTo,
+    // ask for a seed or board to play next
+    NewGame,
     Look,
+    Chord,
     StartGame,
     RestartGame,
+    ExportBoard,
+    GameFinished(GameRecord),
+    GameResult(GameResult),
+    ShowStats,
+    ShowGame,
+    // the errors and warnings so far
+    ShowLog,
+    ScrollUp,
+    ScrollDown,
+    NextPreset,
+    PreviousPreset,
+    ResetStats,
+    Confirm,
+    Cancel,
+}
+
+/// Outcome of a finished game, as sent by `Game` when the game is over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub preset: String,
+    pub won: bool,
+    pub millis: u64,
+    // date of the daily board, if this was one
+    pub daily: Option<String>,
+}
+
+impl Action {
+    // The actions to run for this one with a count typed before its keys: motions are
+    // repeated, jumps to the top or bottom go to that row and jumps to the start of the
+    // row to that column instead. Anything else ignores the count.
+    pub fn counted(self, count: usize) -> Vec<Action> {
+        match self {
+            Action::MoveRight
+            | Action::MoveLeft
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::MoveUpLeft
+            | Action::MoveUpRight
+            | Action::MoveDownLeft
+            | Action::MoveDownRight
+            | Action::MoveNextCovered
+            | Action::MovePreviousCovered
+            | Action::MoveNextFrontier
+            | Action::MovePreviousFrontier => vec![self; count],
+            Action::MoveTop | Action::MoveBottom => vec![Action::MoveToRow(count - 1)],
+            Action::MoveRowStart => vec![Action::MoveToColumn(count - 1)],
+            action => vec![action],
+        }
+    }
 }
@@ -1,15 +1,22 @@
 use color_eyre::Result;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     action::Action,
-    components::{Component, game::Game},
-    config::Config,
-    tui::{Event, Tui},
+    components::{
+        Component,
+        game::{Game, GameOptions, GameResult},
+        notifications::Notifications,
+        stats::Stats,
+        status::StatusLine,
+    },
+    config::{self, Config, get_config_dir, key_event_to_string},
+    tui::{DisplayOptions, Event, Tui},
 };
 
 pub struct App {
@@ -17,36 +24,73 @@ pub struct App {
     components: Vec<Box<dyn Component>>,
     should_quit: bool,
     should_suspend: bool,
+    // capture the mouse for clicking on fields
+    mouse: bool,
+    display: DisplayOptions,
+    // something happened since the last frame was drawn
+    needs_render: bool,
+    // what the status line shows of the pending keys
+    shown_keys: String,
     mode: Mode,
-    last_tick_key_events: Vec<KeyEvent>,
+    // keys typed so far of a sequence that is not complete yet
+    pending_keys: Vec<KeyEvent>,
+    // repetitions typed before the keys
+    count: Option<usize>,
+    pending_since: Option<Instant>,
+    // state of the game when quitting
+    result: Option<GameResult>,
     action_tx: mpsc::UnboundedSender<Action>,
     action_rx: mpsc::UnboundedReceiver<Action>,
 }
 
+// Highest count that can be typed before an action.
+const MAX_COUNT: usize = 999;
+
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Mode {
     #[default]
     Game,
+    Stats,
+    // answering a prompt, e.g. for a field to go to
+    Prompt,
+    // reading the errors and warnings
+    Log,
 }
 
 impl App {
-    pub fn new(num_dinos: usize, height: usize, width: usize) -> Result<Self> {
+    pub fn new(options: GameOptions, display: DisplayOptions, config: Config) -> Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
         Ok(Self {
+            mouse: options.mouse,
+            display,
+            needs_render: true,
+            shown_keys: String::new(),
+            // drawn in order, the statistics cover the game while shown
             components: vec![
-                Box::new(Game::new(width, height, num_dinos)), //default()),
+                Box::new(Game::new(options)), //default()),
+                Box::new(Stats::new()),
+                Box::new(StatusLine::new()),
+                Box::new(Notifications::new()),
             ],
             should_quit: false,
             should_suspend: false,
-            config: Config::new()?,
+            config,
             mode: Mode::Game,
-            last_tick_key_events: Vec::new(),
+            pending_keys: Vec::new(),
+            count: None,
+            pending_since: None,
+            result: None,
             action_tx,
             action_rx,
         })
     }
-    pub async fn run(&mut self) -> Result<()> {
+    // Run until the player quits, returning the result of the last game.
+    pub async fn run(&mut self) -> Result<Option<GameResult>> {
         let mut tui = Tui::new()?;
+        tui.mouse = self.mouse;
+        tui.paste = true;
+        tui.tick_rate = self.display.tick_rate;
+        tui.frame_rate = self.display.frame_rate;
         tui.enter()?;
 
         for component in self.components.iter_mut() {
@@ -59,6 +103,7 @@ impl App {
             component.init(tui.size()?)?;
         }
 
+        let watcher = tokio::spawn(config::watch(get_config_dir(), self.action_tx.clone()));
         let action_tx = self.action_tx.clone();
         loop {
             self.handle_events(&mut tui).await?;
@@ -74,21 +119,32 @@ impl App {
                 break;
             }
         }
+        watcher.abort();
         tui.exit()?;
-        Ok(())
+        Ok(self.result.take())
     }
 
     async fn handle_events(&mut self, tui: &mut Tui) -> Result<()> {
         let Some(event) = tui.next_event().await else {
             return Ok(());
         };
+        self.handle_event(event)
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<()> {
         let action_tx = self.action_tx.clone();
         match event {
             Event::Quit => action_tx.send(Action::Quit)?,
             Event::Tick => action_tx.send(Action::Tick)?,
-            Event::Render => action_tx.send(Action::Render)?,
+            Event::Render if self.should_render() => action_tx.send(Action::Render)?,
             Event::Resize(x, y) => action_tx.send(Action::Resize(x, y))?,
-            Event::Key(key) => self.handle_key_event(key)?,
+            Event::Key(key) => {
+                // typed into an open prompt without any action for it
+                self.needs_render = true;
+                self.handle_key_event(key)?
+            }
+            // goes to the components only, but may change what they show
+            Event::Paste(_) => self.needs_render = true,
             _ => {}
         }
         for component in self.components.iter_mut() {
@@ -99,46 +155,129 @@ impl App {
         Ok(())
     }
 
+    // Keys are collected until they form a bound sequence, which is sent right away, or
+    // until nothing bound starts with them anymore. Digits typed first, unless bound
+    // themselves, give a count for the action, see `Action::counted`; once a count is
+    // started, all digits continue it. While answering a prompt, digits are just that.
     fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
-        let action_tx = self.action_tx.clone();
         let Some(keymap) = self.config.keybindings.get(&self.mode) else {
             return Ok(());
         };
-        match keymap.get(&vec![key]) {
-            Some(action) => {
-                info!("Got action: {action:?}");
-                action_tx.send(action.clone())?;
+        if let KeyCode::Char(digit @ '0'..='9') = key.code
+            && key.modifiers.is_empty()
+            && self.pending_keys.is_empty()
+            && self.mode != Mode::Prompt
+            && (self.count.is_some() || (digit != '0' && !keymap.contains_key(&vec![key])))
+        {
+            let digit = digit as usize - '0' as usize;
+            let count = self.count.unwrap_or(0).saturating_mul(10) + digit;
+            self.count = Some(count.min(MAX_COUNT));
+            self.pending_since = Some(Instant::now());
+            return self.send_pending_keys();
+        }
+
+        self.pending_keys.push(key);
+        let action = keymap.get(&self.pending_keys).cloned();
+        let is_prefix = keymap
+            .keys()
+            .any(|keys| keys.starts_with(&self.pending_keys));
+        if let Some(action) = action {
+            info!("Got action: {action:?}");
+            let actions = match self.count {
+                Some(count) => action.counted(count),
+                None => vec![action],
+            };
+            for action in actions {
+                self.action_tx.send(action)?;
             }
-            _ => {
-                // If the key was not handled as a single key action,
-                // then consider it for multi-key combinations.
-                self.last_tick_key_events.push(key);
-
-                // Check for multi-key combinations
-                if let Some(action) = keymap.get(&self.last_tick_key_events) {
-                    info!("Got action: {action:?}");
-                    action_tx.send(action.clone())?;
-                }
+            self.clear_pending_keys();
+        } else if is_prefix {
+            self.pending_since = Some(Instant::now());
+        } else if self.pending_keys.len() > 1 {
+            // not a known sequence, try again with the last key on its own
+            self.clear_pending_keys();
+            return self.handle_key_event(key);
+        } else {
+            self.clear_pending_keys();
+        }
+        self.send_pending_keys()
+    }
+
+    fn clear_pending_keys(&mut self) {
+        self.pending_keys.clear();
+        self.count = None;
+        self.pending_since = None;
+    }
+
+    // Drop keys that have been waiting for the rest of their sequence for too long.
+    fn expire_pending_keys(&mut self) -> Result<()> {
+        if self
+            .pending_since
+            .is_some_and(|since| since.elapsed() >= self.config.key_timeout())
+        {
+            self.clear_pending_keys();
+            self.send_pending_keys()?;
+        }
+        Ok(())
+    }
+
+    // Let the status line show what has been typed so far.
+    fn send_pending_keys(&self) -> Result<()> {
+        let mut text: String = self.count.map(|c| c.to_string()).unwrap_or_default();
+        for key in &self.pending_keys {
+            match key.code {
+                KeyCode::Char(c) if !key.modifiers.intersects(!KeyModifiers::SHIFT) => text.push(c),
+                _ => text.push_str(&format!("<{}>", key_event_to_string(key))),
             }
         }
+        self.action_tx.send(Action::PendingKeys(text))?;
         Ok(())
     }
 
+    // Frames are drawn at the frame rate, or only when an action may have changed what
+    // is shown if rendering on change.
+    fn should_render(&self) -> bool {
+        !self.display.render_on_change || self.needs_render
+    }
+
+    // Remember whether an action may change what is shown. Most ticks and repeated pending
+    // keys do not.
+    fn note_change(&mut self, action: &Action) {
+        let changes = match action {
+            Action::Render => false,
+            Action::Tick => self.components.iter().any(|c| c.changes_on_tick()),
+            Action::PendingKeys(keys) if *keys == self.shown_keys => false,
+            Action::PendingKeys(keys) => {
+                self.shown_keys = keys.clone();
+                true
+            }
+            _ => true,
+        };
+        self.needs_render |= changes;
+    }
+
     fn handle_actions(&mut self, tui: &mut Tui) -> Result<()> {
         while let Ok(action) = self.action_rx.try_recv() {
             if action != Action::Tick && action != Action::Render {
                 debug!("{action:?}");
             }
+            self.note_change(&action);
             match action {
-                Action::Tick => {
-                    self.last_tick_key_events.drain(..);
-                }
+                Action::Tick => self.expire_pending_keys()?,
                 Action::Quit => self.should_quit = true,
                 Action::Suspend => self.should_suspend = true,
                 Action::Resume => self.should_suspend = false,
                 Action::ClearScreen => tui.terminal.clear()?,
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
+                Action::ShowStats => self.mode = Mode::Stats,
+                Action::ShowGame => self.mode = Mode::Game,
+                Action::GoTo | Action::NewGame => self.mode = Mode::Prompt,
+                Action::ShowLog => self.mode = Mode::Log,
+                Action::Error(ref message) => error!("{message}"),
+                Action::Warning(ref message) => warn!("{message}"),
+                Action::GameResult(ref result) => self.result = Some(result.clone()),
+                Action::ReloadConfig => self.reload_config()?,
                 _ => {}
             }
             for component in self.components.iter_mut() {
@@ -150,6 +289,31 @@ impl App {
         Ok(())
     }
 
+    // Apply the config files as they are now, keeping the current config if they have
+    // problems.
+    fn reload_config(&mut self) -> Result<()> {
+        let (config, report) = Config::load(&get_config_dir());
+        if let Some(first) = report.diagnostics.first() {
+            let more = match report.diagnostics.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            self.action_tx.send(Action::Warning(format!(
+                "config not reloaded: {first}{more}"
+            )))?;
+            return Ok(());
+        }
+        for component in self.components.iter_mut() {
+            component.register_config_handler(config.clone())?;
+        }
+        self.config = config;
+        self.clear_pending_keys();
+        self.send_pending_keys()?;
+        self.action_tx
+            .send(Action::Status("configuration reloaded".into()))?;
+        Ok(())
+    }
+
     fn handle_resize(&mut self, tui: &mut Tui, w: u16, h: u16) -> Result<()> {
         tui.resize(Rect::new(0, 0, w, h))?;
         self.render(tui)?;
@@ -157,6 +321,7 @@ impl App {
     }
 
     fn render(&mut self, tui: &mut Tui) -> Result<()> {
+        self.needs_render = false;
         tui.draw(|frame| {
             for component in self.components.iter_mut() {
                 if let Err(err) = component.draw(frame, frame.area()) {
@@ -169,3 +334,149 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::parse_key_sequence;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut config = Config::default();
+        let bindings = [
+            ("l", Action::MoveRight),
+            ("<g><g>", Action::MoveUp),
+            ("x", Action::Look),
+            ("0", Action::MoveRowStart),
+            ("<Shift-g>", Action::MoveBottom),
+        ]
+        .into_iter()
+        .map(|(keys, action)| (parse_key_sequence(keys).unwrap(), action))
+        .collect();
+        config.keybindings.insert(Mode::Game, bindings);
+        App::new(GameOptions::default(), DisplayOptions::default(), config).unwrap()
+    }
+
+    fn type_keys(app: &mut App, keys: &str) -> Vec<Action> {
+        for key in parse_key_sequence(keys).unwrap() {
+            app.handle_key_event(key).unwrap();
+        }
+        let mut actions = vec![];
+        while let Ok(action) = app.action_rx.try_recv() {
+            actions.push(action);
+        }
+        actions
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut app = app();
+        assert_eq!(
+            type_keys(&mut app, "<g>"),
+            [Action::PendingKeys("g".into())]
+        );
+        assert_eq!(
+            type_keys(&mut app, "<g>"),
+            [Action::MoveUp, Action::PendingKeys(String::new())]
+        );
+        // a key that does not continue the sequence counts on its own
+        assert_eq!(
+            type_keys(&mut app, "<g><l>"),
+            [
+                Action::PendingKeys("g".into()),
+                Action::MoveRight,
+                Action::PendingKeys(String::new())
+            ]
+        );
+        assert_eq!(
+            type_keys(&mut app, "<q>"),
+            [Action::PendingKeys(String::new())]
+        );
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut app = app();
+        let actions = type_keys(&mut app, "<1><2><l>");
+        assert_eq!(
+            actions[..2],
+            [
+                Action::PendingKeys("1".into()),
+                Action::PendingKeys("12".into())
+            ]
+        );
+        assert_eq!(actions[2..14], vec![Action::MoveRight; 12]);
+        // only motions are repeated
+        let actions = type_keys(&mut app, "<3><x>");
+        assert_eq!(actions.iter().filter(|a| **a == Action::Look).count(), 1);
+        // a leading zero is not a count, but a zero within one is
+        assert_eq!(
+            type_keys(&mut app, "<0>"),
+            [Action::MoveRowStart, Action::PendingKeys(String::new())]
+        );
+        let actions = type_keys(&mut app, "<1><0><l>");
+        assert_eq!(
+            actions.iter().filter(|a| **a == Action::MoveRight).count(),
+            10
+        );
+        assert!(!actions.contains(&Action::MoveRowStart));
+        // jumps take the count as a row
+        assert!(type_keys(&mut app, "<1><2><Shift-g>").contains(&Action::MoveToRow(11)));
+    }
+
+    #[test]
+    fn test_should_render() {
+        let mut app = app();
+        assert!(app.should_render());
+        app.needs_render = false;
+        // drawn at the frame rate unless asked otherwise
+        assert!(app.should_render());
+        app.display.render_on_change = true;
+        assert!(!app.should_render());
+
+        // an idle board does not change with the ticks
+        app.note_change(&Action::Tick);
+        app.note_change(&Action::PendingKeys(String::new()));
+        assert!(!app.should_render());
+        app.note_change(&Action::PendingKeys("g".into()));
+        assert!(app.should_render());
+        app.needs_render = false;
+        app.note_change(&Action::PendingKeys("g".into()));
+        assert!(!app.should_render());
+        app.note_change(&Action::MoveRight);
+        assert!(app.should_render());
+
+        // while the clock runs every tick does
+        app.needs_render = false;
+        app.components[0].update(Action::StartGame).unwrap();
+        app.note_change(&Action::Tick);
+        assert!(app.should_render());
+        app.needs_render = false;
+        app.note_change(&Action::Render);
+        assert!(!app.should_render());
+
+        // keys typed into a prompt only reach the game
+        app.components[0].update(Action::GoTo).unwrap();
+        app.mode = Mode::Prompt;
+        for key in parse_key_sequence("<c><Backspace>").unwrap() {
+            app.needs_render = false;
+            app.handle_event(Event::Key(key)).unwrap();
+            assert!(app.should_render());
+        }
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut app = app();
+        app.config.key_timeout = Some(Duration::from_millis(100));
+        type_keys(&mut app, "<5><g>");
+        app.expire_pending_keys().unwrap();
+        assert_eq!(app.pending_keys.len(), 1);
+        app.pending_since = Instant::now().checked_sub(Duration::from_millis(100));
+        app.expire_pending_keys().unwrap();
+        assert!(app.pending_keys.is_empty());
+        assert_eq!(app.count, None);
+        // the sequence starts over
+        assert!(type_keys(&mut app, "<g><l>").contains(&Action::MoveRight));
+    }
+}
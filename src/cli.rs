@@ -1,19 +1,217 @@
-use crate::config::{get_config_dir, get_data_dir};
-use clap::Parser;
+use crate::{
+    components::{
+        game::{GameOptions, Preset, Theme},
+        lib::{
+            board::Board,
+            daily::{DAILY_DINOS, DAILY_HEIGHT, DAILY_WIDTH, Daily},
+            dinomite::Charset,
+            formats::BoardFormat,
+            topology::{Neighborhood, TopologyKind},
+        },
+    },
+    config::{DisplayConfig, GameConfig, get_config_dir, get_data_dir},
+    tui::{DisplayOptions, MAX_RATE},
+};
+use clap::{Parser, Subcommand};
 use clap_num::number_range;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
 pub struct Cli {
-    /// Number of dinos hidden (max 99)
-    #[arg(long, value_name = "INT", default_value_t = 10, value_parser=constraint_99)]
-    pub num_dinos: usize,
-    /// Height of the board (max 20)
-    #[arg(long, value_name = "INT", default_value_t = 9, value_parser=constraint_20)]
-    pub height: usize,
-    /// Width of the board (max 30)
-    #[arg(long, value_name = "INT", default_value_t = 9, value_parser=constraint_30)]
-    pub width: usize,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+    /// Number of dinos hidden (at most width x height x dinos per field) [default: 10]
+    #[arg(long, value_name = "INT", value_parser=constraint_dinos)]
+    pub num_dinos: Option<usize>,
+    /// Height of the board (max 1000) [default: 9]
+    #[arg(long, value_name = "INT", value_parser=constraint_height)]
+    pub height: Option<usize>,
+    /// Width of the board (max 1000) [default: 9]
+    #[arg(long, value_name = "INT", value_parser=constraint_width)]
+    pub width: Option<usize>,
+    /// Board size and number of dinos of the classic game
+    #[arg(long, value_enum, conflicts_with_all = ["num_dinos", "height", "width"])]
+    pub preset: Option<Preset>,
+    /// Allow placing more flags than there are dinos
+    #[arg(long)]
+    pub unlimited_flags: bool,
+    /// Never hide a dino on or next to the first uncovered field
+    #[arg(long, overrides_with = "no_safe_start")]
+    pub safe_start: bool,
+    /// Allow dinos on and next to the first uncovered field, even if the config asks otherwise
+    #[arg(long, overrides_with = "safe_start")]
+    pub no_safe_start: bool,
+    /// How the edges of the board are connected
+    #[arg(long, value_enum, default_value_t = TopologyKind::Rectangular)]
+    pub topology: TopologyKind,
+    /// Which fields count towards a number (square boards only)
+    #[arg(long, value_enum, default_value_t = Neighborhood::King)]
+    pub neighborhood: Neighborhood,
+    /// How many dinos may hide in a single field
+    #[arg(long, value_name = "INT", default_value_t = 1, value_parser=constraint_dinos_per_field)]
+    pub dinos_per_field: u8,
+    /// Play the layout from a file instead of a random one: text (`*` for a dino, `.` for a
+    /// safe field), or .mbf and .rawvf as used by other minesweeper tools. The moves in a
+    /// .rawvf replay are played on a rectangular board
+    #[arg(long, value_name = "PATH", value_parser=board_from_file,
+          conflicts_with_all = ["num_dinos", "height", "width", "preset", "dinos_per_field"])]
+    pub board: Option<Board>,
+    /// Play today's daily board, the same for everybody (16x16 with 40 dinos)
+    #[arg(long, conflicts_with_all = [
+        "num_dinos", "height", "width", "preset", "dinos_per_field", "board", "topology",
+        "neighborhood", "safe_start",
+    ])]
+    pub daily: bool,
+    /// Write a JSON summary of the game to PATH on exit, or to stdout for `-` (not with
+    /// --headless-protocol). The exit code tells the outcome either way: 0 won, 2 lost, 3 quit
+    #[arg(long, value_name = "PATH")]
+    pub result_json: Option<PathBuf>,
+    /// Play without the terminal interface, reading commands such as `open 3 4`, `flag 2 2`,
+    /// `chord 5 5` or `state` from stdin and answering on stdout, e.g. for bots
+    #[arg(long)]
+    pub headless_protocol: bool,
+    /// Format used when exporting the current game. Boards with other topologies or
+    /// neighborhoods can only be exported as text
+    #[arg(long, value_enum, default_value_t = BoardFormat::Text)]
+    pub export_format: BoardFormat,
+    /// Symbols to draw the board with [default: emoji]
+    #[arg(long, value_enum)]
+    pub charset: Option<Charset>,
+    /// Colors to suit the terminal's background [default: dark]
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+    /// Open, flag and chord fields with the left, right and middle mouse button
+    #[arg(long, overrides_with = "no_mouse")]
+    pub mouse: bool,
+    /// Leave the mouse to the terminal, e.g. for selecting text
+    #[arg(long, overrides_with = "mouse")]
+    pub no_mouse: bool,
+    /// Go on from flags to a question mark when flagging a field
+    #[arg(long, overrides_with = "no_question_marks")]
+    pub question_marks: bool,
+    /// Go from flags straight back to covered fields
+    #[arg(long, overrides_with = "question_marks")]
+    pub no_question_marks: bool,
+    /// Name the rows and columns around the board, e.g. C7
+    #[arg(long, overrides_with = "no_labels")]
+    pub labels: bool,
+    /// Draw the board without row and column names
+    #[arg(long, overrides_with = "labels")]
+    pub no_labels: bool,
+    /// Name to report along with the result
+    #[arg(long, value_name = "NAME")]
+    pub player_name: Option<String>,
+    /// Updates per second, e.g. of the timer [default: 4]
+    #[arg(long, value_name = "FLOAT", value_parser=constraint_rate)]
+    pub tick_rate: Option<f64>,
+    /// Redraws per second [default: 60]
+    #[arg(long, value_name = "FLOAT", value_parser=constraint_rate)]
+    pub frame_rate: Option<f64>,
+    /// Only redraw when something changed, e.g. to save bandwidth over slow connections
+    #[arg(long, overrides_with = "no_render_on_change")]
+    pub render_on_change: bool,
+    /// Redraw at the frame rate, whether something changed or not
+    #[arg(long, overrides_with = "render_on_change")]
+    pub no_render_on_change: bool,
+}
+
+impl Cli {
+    // Settings for the game, taken from the arguments where given, else from the game
+    // section of the config. Sizes given explicitly win over presets on either level.
+    pub fn game_options(&self, game: &GameConfig) -> GameOptions {
+        let defaults = GameOptions::default();
+        let cli_preset = self.preset.map(Preset::size);
+        let config_preset = game.preset.map(Preset::size);
+        let size =
+            |cli: Option<usize>, pick: fn((usize, usize, usize)) -> usize, config, default| {
+                cli.or(cli_preset.map(pick))
+                    .or(config)
+                    .or(config_preset.map(pick))
+                    .unwrap_or(default)
+            };
+        let mut options = GameOptions {
+            width: size(self.width, |p| p.0, game.width, defaults.width),
+            height: size(self.height, |p| p.1, game.height, defaults.height),
+            num_dinos: size(self.num_dinos, |p| p.2, game.num_dinos, defaults.num_dinos),
+            flag_limit: !self.unlimited_flags,
+            safe_start: switch(self.safe_start, self.no_safe_start)
+                .or(game.safe_start)
+                .unwrap_or(defaults.safe_start),
+            topology: self.topology,
+            neighborhood: self.neighborhood,
+            dinos_per_field: self.dinos_per_field,
+            board: self.board.clone(),
+            export_format: self.export_format,
+            daily: None,
+            charset: self.charset.or(game.charset).unwrap_or(defaults.charset),
+            theme: self.theme.or(game.theme).unwrap_or(defaults.theme),
+            mouse: switch(self.mouse, self.no_mouse)
+                .or(game.mouse)
+                .unwrap_or(defaults.mouse),
+            question_marks: switch(self.question_marks, self.no_question_marks)
+                .or(game.question_marks)
+                .unwrap_or(defaults.question_marks),
+            player_name: self.player_name.clone().or(game.player_name.clone()),
+            labels: switch(self.labels, self.no_labels)
+                .or(game.labels)
+                .unwrap_or(defaults.labels),
+        };
+        if self.daily {
+            // the same board for everybody, whatever is configured
+            options = GameOptions {
+                width: DAILY_WIDTH,
+                height: DAILY_HEIGHT,
+                num_dinos: DAILY_DINOS,
+                safe_start: false,
+                daily: Some(Daily::today()),
+                ..options
+            };
+        }
+        options
+    }
+    // Rates for the interface, taken from the arguments where given, else from the display
+    // section of the config.
+    pub fn display_options(&self, display: &DisplayConfig) -> DisplayOptions {
+        let defaults = DisplayOptions::default();
+        DisplayOptions {
+            tick_rate: self
+                .tick_rate
+                .or(display.tick_rate)
+                .unwrap_or(defaults.tick_rate),
+            frame_rate: self
+                .frame_rate
+                .or(display.frame_rate)
+                .unwrap_or(defaults.frame_rate),
+            render_on_change: switch(self.render_on_change, self.no_render_on_change)
+                .or(display.render_on_change)
+                .unwrap_or(defaults.render_on_change),
+        }
+    }
+}
+
+// A setting turned on with `--name` or off with `--no-name`, None if neither was given.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Commands {
+    /// Work with the configuration files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigCommands {
+    /// Report invalid and conflicting key bindings in the configuration files
+    Check,
 }
 
 const VERSION_MESSAGE: &str = concat!(
@@ -43,12 +241,226 @@ Data directory: {data_dir_path}"
     )
 }
 
-fn constraint_20(s: &str) -> Result<usize, String> {
-    number_range(s, 5, 20)
+pub const MIN_SIZE: usize = 5;
+pub const MAX_WIDTH: usize = 1000;
+pub const MAX_HEIGHT: usize = 1000;
+pub const MAX_DINOS_PER_FIELD: u8 = 9;
+
+fn constraint_height(s: &str) -> Result<usize, String> {
+    number_range(s, MIN_SIZE, MAX_HEIGHT)
+}
+fn constraint_width(s: &str) -> Result<usize, String> {
+    number_range(s, MIN_SIZE, MAX_WIDTH)
+}
+fn constraint_dinos(s: &str) -> Result<usize, String> {
+    number_range(s, 0, MAX_WIDTH * MAX_HEIGHT * MAX_DINOS_PER_FIELD as usize)
+}
+fn board_from_file(path: &str) -> Result<Board, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let board = BoardFormat::from_path(path.as_ref())
+        .read(&bytes)
+        .map_err(|e| e.to_string())?;
+    check_board_size(&board)?;
+    Ok(board)
+}
+// Boards read or pasted have to fit the sizes that can be asked for.
+pub fn check_board_size(board: &Board) -> Result<(), String> {
+    if !(MIN_SIZE..=MAX_WIDTH).contains(&board.width)
+        || !(MIN_SIZE..=MAX_HEIGHT).contains(&board.height)
+    {
+        return Err(format!(
+            "the board is {}x{}, but has to be between {MIN_SIZE}x{MIN_SIZE} and {MAX_WIDTH}x{MAX_HEIGHT}",
+            board.width, board.height
+        ));
+    }
+    Ok(())
 }
-fn constraint_30(s: &str) -> Result<usize, String> {
-    number_range(s, 5, 30)
+// The dinos asked for have to fit on the board, however the options were put together from
+// the arguments, the config and the presets. Boards from files bring their own dinos.
+pub fn check_num_dinos(options: &GameOptions) -> Result<(), String> {
+    let capacity = options.width * options.height * options.dinos_per_field as usize;
+    if options.board.is_none() && options.num_dinos > capacity {
+        return Err(format!(
+            "{} dinos do not fit on the {}x{} board with {} per field, it holds at most {capacity}",
+            options.num_dinos, options.width, options.height, options.dinos_per_field
+        ));
+    }
+    Ok(())
 }
-fn constraint_99(s: &str) -> Result<usize, String> {
-    number_range(s, 0, 99)
+fn constraint_dinos_per_field(s: &str) -> Result<u8, String> {
+    number_range(s, 1, MAX_DINOS_PER_FIELD)
+}
+fn constraint_rate(s: &str) -> Result<f64, String> {
+    let rate: f64 = s.parse().map_err(|_| format!("`{s}` is not a number"))?;
+    if !(rate > 0.0 && rate <= MAX_RATE) {
+        return Err(format!("has to be more than 0 and at most {MAX_RATE}"));
+    }
+    Ok(rate)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn options(args: &[&str], game: &GameConfig) -> GameOptions {
+        let args = ["dinomite-cmd"].iter().chain(args);
+        Cli::try_parse_from(args).unwrap().game_options(game)
+    }
+
+    #[test]
+    fn test_game_options_layering() {
+        let game = GameConfig {
+            width: Some(20),
+            preset: Some(Preset::Intermediate),
+            charset: Some(Charset::Ascii),
+            safe_start: Some(true),
+            mouse: Some(true),
+            labels: Some(false),
+            player_name: Some("ada".to_string()),
+            ..Default::default()
+        };
+        let defaults = options(&[], &GameConfig::default());
+        assert_eq!(
+            (defaults.width, defaults.height, defaults.num_dinos),
+            (9, 9, 10)
+        );
+        assert_eq!(defaults.charset, Charset::Emoji);
+
+        let configured = options(&[], &game);
+        assert_eq!((configured.width, configured.height), (20, 16));
+        assert_eq!(configured.num_dinos, 40);
+        assert_eq!(configured.charset, Charset::Ascii);
+        assert!(configured.safe_start);
+        assert!(configured.mouse);
+        assert!(!configured.labels);
+
+        let overridden = options(
+            &[
+                "--width",
+                "12",
+                "--charset",
+                "emoji",
+                "--player-name",
+                "bob",
+            ],
+            &game,
+        );
+        assert_eq!((overridden.width, overridden.height), (12, 16));
+        assert_eq!(overridden.charset, Charset::Emoji);
+        assert_eq!(overridden.player_name.as_deref(), Some("bob"));
+
+        // switches given on the command line win over the config either way
+        let switched = options(&["--no-safe-start", "--no-mouse", "--labels"], &game);
+        assert!(!switched.safe_start);
+        assert!(!switched.mouse);
+        assert!(switched.labels);
+        // the last one given counts
+        let last = options(&["--question-marks", "--no-question-marks"], &game);
+        assert!(!last.question_marks);
+        let last = options(&["--no-mouse", "--mouse"], &game);
+        assert!(last.mouse);
+
+        let preset = options(&["--preset", "expert"], &game);
+        assert_eq!(
+            (preset.width, preset.height, preset.num_dinos),
+            (30, 16, 99)
+        );
+
+        let daily = options(&["--daily"], &game);
+        assert_eq!((daily.width, daily.height, daily.num_dinos), (16, 16, 40));
+        assert!(!daily.safe_start);
+    }
+
+    #[test]
+    fn test_board_conflicts_with_sizes() {
+        let path = std::env::temp_dir().join("dinomite-test-board-conflicts.txt");
+        std::fs::write(&path, ".....\n.....\n.....\n.....\n....*\n").unwrap();
+        let path = path.to_str().unwrap();
+        for args in [["--preset", "expert"], ["--width", "12"]] {
+            let args = ["dinomite-cmd", "--board", path].into_iter().chain(args);
+            let error = Cli::try_parse_from(args).unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+
+    #[test]
+    fn test_num_dinos_fit_the_board() {
+        let check = |args: &[&str], game: &GameConfig| check_num_dinos(&options(args, game));
+        let error = check(
+            &["--width", "9", "--height", "9", "--num-dinos", "5000"],
+            &Default::default(),
+        );
+        assert_eq!(
+            error.unwrap_err(),
+            "5000 dinos do not fit on the 9x9 board with 1 per field, it holds at most 81"
+        );
+        assert!(check(&["--num-dinos", "81"], &Default::default()).is_ok());
+        // several dinos per field make room for more
+        assert!(
+            check(
+                &["--num-dinos", "243", "--dinos-per-field", "3"],
+                &Default::default()
+            )
+            .is_ok()
+        );
+        assert!(
+            check(
+                &["--num-dinos", "244", "--dinos-per-field", "3"],
+                &Default::default()
+            )
+            .is_err()
+        );
+        // the same for configured values and presets
+        let game = GameConfig {
+            num_dinos: Some(500),
+            ..Default::default()
+        };
+        assert!(check(&[], &game).is_err());
+        assert!(check(&["--preset", "expert"], &game).is_ok());
+        let game = GameConfig {
+            width: Some(5),
+            height: Some(5),
+            ..Default::default()
+        };
+        assert!(check(&["--preset", "expert"], &game).is_ok());
+        assert!(check(&["--num-dinos", "30"], &game).is_err());
+    }
+
+    #[test]
+    fn test_display_options() {
+        let display = DisplayConfig {
+            frame_rate: Some(10.0),
+            tick_rate: Some(2.0),
+            ..Default::default()
+        };
+        let parse = |args: &[&str]| {
+            let args = ["dinomite-cmd"].iter().chain(args);
+            Cli::try_parse_from(args)
+        };
+        let defaults = parse(&[])
+            .unwrap()
+            .display_options(&DisplayConfig::default());
+        assert_eq!(defaults, DisplayOptions::default());
+        let options = parse(&["--frame-rate", "2.5", "--render-on-change"])
+            .unwrap()
+            .display_options(&display);
+        assert_eq!(
+            options,
+            DisplayOptions {
+                tick_rate: 2.0,
+                frame_rate: 2.5,
+                render_on_change: true,
+            }
+        );
+        let configured = DisplayConfig {
+            render_on_change: Some(true),
+            ..display
+        };
+        let options = parse(&["--no-render-on-change"])
+            .unwrap()
+            .display_options(&configured);
+        assert!(!options.render_on_change);
+        assert!(parse(&["--frame-rate", "0"]).is_err());
+        assert!(parse(&["--tick-rate", "fast"]).is_err());
+    }
 }
@@ -10,6 +10,9 @@ use crate::{action::Action, config::Config, tui::Event};
 
 pub mod game;
 pub mod lib;
+pub mod notifications;
+pub mod stats;
+pub mod status;
 /// `Component` is a trait that represents a visual and interactive element of the user interface.
 ///
 /// Implementors of this trait can be registered with the main application loop and will be able to
@@ -67,6 +70,7 @@ pub trait Component {
         let action = match event {
             Some(Event::Key(key_event)) => self.handle_key_event(key_event)?,
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_event(mouse_event)?,
+            Some(Event::Paste(text)) => self.handle_paste(text)?,
             _ => None,
         };
         Ok(action)
@@ -97,6 +101,19 @@ pub trait Component {
         let _ = mouse; // to appease clippy
         Ok(None)
     }
+    /// Handle text pasted into the terminal and produce actions if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The pasted text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<Action>>` - An action to be processed or none.
+    fn handle_paste(&mut self, text: String) -> Result<Option<Action>> {
+        let _ = text; // to appease clippy
+        Ok(None)
+    }
     /// Update the state of the component based on a received action. (REQUIRED)
     ///
     /// # Arguments
@@ -110,6 +127,15 @@ pub trait Component {
         let _ = action; // to appease clippy
         Ok(None)
     }
+    /// Whether the next tick changes what is drawn, e.g. a running clock or a message that
+    /// is due to disappear.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if a frame should be drawn after the tick.
+    fn changes_on_tick(&self) -> bool {
+        false
+    }
     /// Render the component on the screen. (REQUIRED)
     ///
     /// # Arguments
@@ -1,42 +1,322 @@
-use super::{Component, lib::dinomite::Dinomite};
-use crate::components::lib::dinomite;
+use super::{Component, lib::dinomite::Dinomite, stats::Statistics};
+use crate::components::lib::{
+    analysis::{self, BoardStats},
+    board::Board,
+    daily::Daily,
+    dinomite::{self, Charset, FieldView, GameStatus},
+    formats::BoardFormat,
+    replay::{Move, Replay},
+    topology::{self, Neighborhood, TopologyKind},
+};
+use clap::ValueEnum;
 use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{prelude::*, widgets::*};
-use std::time::Instant;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    ops::Range,
+    path::PathBuf,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 use tokio::sync::mpsc::UnboundedSender;
+use tracing::info;
 
-use crate::{action::Action, config::Config};
+use crate::{
+    action::{Action, GameRecord},
+    cli::{MAX_HEIGHT, MAX_WIDTH, check_board_size},
+    config::{Config, get_data_dir},
+};
+
+// Terminal columns taken up by a single field, e.g. " 🦖 "
+const CELL_WIDTH: usize = 4;
 
 #[derive(Default)]
 pub struct Game {
     command_tx: Option<UnboundedSender<Action>>,
     config: Config,
     gamestate: GameState,
-    cells: Vec<Vec<char>>,
+    // top left field of the visible part of the board
+    scroll: dinomite::Position,
     dinomite: Dinomite,
-    width: usize,
-    height: usize,
-    num_dinos: usize,
+    // moves made in the current game
+    replay: Replay,
+    // analysis of the layout, once the game is over
+    stats: Option<BoardStats>,
+    // fastest earlier win on today's daily board
+    daily_best: Option<u64>,
+    // where the board was drawn last, for finding the field under the mouse
+    board_area: Rect,
+    // what is being asked for, with the text typed or pasted so far
+    prompt: Option<(Prompt, String)>,
+    // the statistics or the log are shown over the board
+    covered: bool,
+    options: GameOptions,
+}
+
+/// Board sizes known from the classic game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Preset {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Preset {
+    // Width, height and number of dinos.
+    pub fn size(self) -> (usize, usize, usize) {
+        match self {
+            Preset::Beginner => (9, 9, 10),
+            Preset::Intermediate => (16, 16, 40),
+            Preset::Expert => (30, 16, 99),
+        }
+    }
+}
+
+/// Colors the board is drawn in, to suit the terminal's background.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, strum::Display, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn hint(self) -> Color {
+        match self {
+            Theme::Dark => Color::DarkGray,
+            Theme::Light => Color::Gray,
+        }
+    }
+    // Color of the number of dinos nearby, roughly as in the classic game.
+    fn number(self, count: u16) -> Color {
+        match (self, count) {
+            (Theme::Dark, 1) => Color::LightBlue,
+            (Theme::Dark, 2) => Color::LightGreen,
+            (Theme::Dark, 3) => Color::LightRed,
+            (Theme::Dark, 4) => Color::LightMagenta,
+            (Theme::Dark, 5) => Color::Yellow,
+            (Theme::Dark, 6) => Color::LightCyan,
+            (Theme::Dark, _) => Color::White,
+            (Theme::Light, 1) => Color::Blue,
+            (Theme::Light, 2) => Color::Green,
+            (Theme::Light, 3) => Color::Red,
+            (Theme::Light, 4) => Color::Magenta,
+            (Theme::Light, 5) => Color::Indexed(94),
+            (Theme::Light, 6) => Color::Cyan,
+            (Theme::Light, _) => Color::Black,
+        }
+    }
+}
+
+// Questions asked over the board, answered by typing or pasting a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    // a field to move the cursor to
+    GoTo,
+    // a seed or a board layout to play next
+    NewGame,
+}
+
+impl Prompt {
+    fn title(self) -> &'static str {
+        match self {
+            Prompt::GoTo => "Go to field",
+            Prompt::NewGame => "New game from seed or board",
+        }
+    }
+    fn example(self) -> &'static str {
+        match self {
+            Prompt::GoTo => " e.g. C7 or 2 6 ",
+            Prompt::NewGame => " e.g. 42 or *..../...*./..... ",
+        }
+    }
+    // Longest text that can be typed, boards are usually pasted.
+    fn max_len(self) -> usize {
+        match self {
+            Prompt::GoTo => 16,
+            Prompt::NewGame => (MAX_WIDTH + 1) * MAX_HEIGHT * 2,
+        }
+    }
+}
+
+// How new boards are set up and shown, kept around for restarting.
+#[derive(Debug, Clone)]
+pub struct GameOptions {
+    pub width: usize,
+    pub height: usize,
+    pub num_dinos: usize,
+    pub flag_limit: bool,
+    pub safe_start: bool,
+    pub topology: TopologyKind,
+    pub neighborhood: Neighborhood,
+    pub dinos_per_field: u8,
+    // a fixed layout to play instead of a random one
+    pub board: Option<Board>,
+    pub export_format: BoardFormat,
+    // play the board of the day instead of a random one
+    pub daily: Option<Daily>,
+    pub charset: Charset,
+    pub theme: Theme,
+    // open, flag and chord fields by clicking them
+    pub mouse: bool,
+    // flagging a field goes on to a question mark before clearing it
+    pub question_marks: bool,
+    // reported along with the result
+    pub player_name: Option<String>,
+    // column letters and row numbers around the board
+    pub labels: bool,
+}
+impl GameOptions {
+    // Name under which results on boards like this are collected, e.g. "expert" or
+    // "12x10/20 torus".
+    pub fn preset(&self) -> String {
+        if self.daily.is_some() {
+            return "daily".to_string();
+        }
+        let mut name = match &self.board {
+            Some(board) => format!(
+                "{}x{}/{} board",
+                board.width,
+                board.height,
+                board.num_dinos()
+            ),
+            None => {
+                let size = (self.width, self.height, self.num_dinos);
+                match Preset::value_variants().iter().find(|p| p.size() == size) {
+                    Some(preset) => preset.to_string(),
+                    None => format!("{}x{}/{}", size.0, size.1, size.2),
+                }
+            }
+        };
+        if self.topology != TopologyKind::Rectangular {
+            name.push_str(&format!(" {}", self.topology));
+        }
+        if self.neighborhood != Neighborhood::King {
+            name.push_str(&format!(" {}", self.neighborhood));
+        }
+        if self.dinos_per_field > 1 && self.board.is_none() {
+            name.push_str(&format!(" x{}", self.dinos_per_field));
+        }
+        name
+    }
+}
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            width: 9,
+            height: 9,
+            num_dinos: 10,
+            flag_limit: true,
+            safe_start: false,
+            topology: TopologyKind::Rectangular,
+            neighborhood: Neighborhood::King,
+            dinos_per_field: 1,
+            board: None,
+            export_format: BoardFormat::Text,
+            daily: None,
+            charset: Charset::Emoji,
+            theme: Theme::Dark,
+            mouse: false,
+            question_marks: false,
+            player_name: None,
+            labels: false,
+        }
+    }
 }
 impl Game {
-    pub fn new(width: usize, height: usize, num_dinos: usize) -> Self {
+    pub fn new(mut options: GameOptions) -> Self {
+        let dinomite = Self::new_dinomite(&mut options);
+        let daily_best = options.daily.as_ref().and_then(|daily| {
+            let statistics = Statistics::load().ok()?;
+            statistics.daily.get(&daily.date)?.best_millis
+        });
         Self {
             command_tx: Default::default(),
             config: Default::default(),
             gamestate: Default::default(),
-            cells: Default::default(),
-            width,
-            height,
-            num_dinos,
-            dinomite: Dinomite::new(width, height, num_dinos),
+            scroll: dinomite::Position(0, 0),
+            dinomite,
+            replay: Replay::default(),
+            stats: None,
+            daily_best,
+            board_area: Rect::default(),
+            prompt: None,
+            covered: false,
+            options,
+        }
+    }
+    // The board for a new game. Progress saved with a fixed layout is only restored the
+    // first time, restarting plays the layout from the beginning.
+    fn new_dinomite(options: &mut GameOptions) -> Dinomite {
+        let topology = options.topology.topology(options.neighborhood);
+        if let Some(board) = &mut options.board {
+            let mut dinomite = Dinomite::from_board(board, topology);
+            dinomite.set_flag_limit(options.flag_limit);
+            dinomite.set_question_marks(options.question_marks);
+            board.state = None;
+            return dinomite;
+        }
+        let mut dinomite =
+            Dinomite::with_topology(options.width, options.height, options.num_dinos, topology);
+        dinomite.set_flag_limit(options.flag_limit);
+        dinomite.set_question_marks(options.question_marks);
+        dinomite.set_safe_start(options.safe_start);
+        dinomite.set_dinos_per_field(options.dinos_per_field);
+        if let Some(daily) = &options.daily {
+            dinomite.set_seed(daily.seed);
+        }
+        dinomite
+    }
+}
+/// How a game ended, or that it was left unfinished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Won,
+    Lost,
+    Quit,
+}
+
+impl Outcome {
+    // Process exit code reporting this outcome.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Won => 0,
+            Outcome::Lost => 2,
+            Outcome::Quit => 3,
         }
     }
 }
+
+/// Summary of the game when the application exits, for scripts and dashboards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    pub millis: u64,
+    pub width: usize,
+    pub height: usize,
+    pub num_dinos: usize,
+    // None for imported boards and before the first move
+    pub seed: Option<u64>,
+    pub clicks: usize,
+    pub flags: usize,
+    // None before the dinos are hidden
+    pub bbbv: Option<usize>,
+    pub player: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct GameState {
     pub curpos: dinomite::Position,
     pub game_start: Option<Instant>,
-    pub elapsed_seconds: u64,
+    pub elapsed_millis: u64,
     pub is_game_over: bool,
 }
 
@@ -46,7 +326,7 @@ impl Default for GameState {
         GameState {
             curpos: default_pos,
             game_start: None,
-            elapsed_seconds: 0,
+            elapsed_millis: 0,
             is_game_over: false,
         }
     }
@@ -58,56 +338,339 @@ impl GameState {
         }
     }
     pub fn update_timer(&mut self) {
-        if let Some(start_time) = self.game_start {
-            if !self.is_game_over {
-                self.elapsed_seconds = start_time.elapsed().as_secs();
-            }
+        if let Some(start_time) = self.game_start
+            && !self.is_game_over
+        {
+            self.elapsed_millis = start_time.elapsed().as_millis() as u64;
         }
     }
     fn reset(&mut self) {
         self.game_start = None;
-        self.elapsed_seconds = 0;
+        self.elapsed_millis = 0;
         self.is_game_over = false;
     }
 }
 
 impl Game {
-    fn align(c: char) -> String {
-        let res = match c {
-            '1' => "１",
-            '2' => "２",
-            '3' => "３",
-            '4' => "４",
-            '5' => "５",
-            '6' => "６",
-            '7' => "７",
-            '8' => "８",
-            _ => &format!("{}", c),
+    fn align(symbol: &str) -> &str {
+        match symbol {
+            "1" => "１",
+            "2" => "２",
+            "3" => "３",
+            "4" => "４",
+            "5" => "５",
+            "6" => "６",
+            "7" => "７",
+            "8" => "８",
+            "9" => "９",
+            _ => symbol,
+        }
+    }
+    pub fn dinomite(&self) -> &Dinomite {
+        &self.dinomite
+    }
+    // Summary of the game as it stands.
+    pub fn result(&self) -> GameResult {
+        let outcome = match self.dinomite.status() {
+            GameStatus::Won => Outcome::Won,
+            GameStatus::Lost { .. } => Outcome::Lost,
+            GameStatus::NotStarted | GameStatus::Playing => Outcome::Quit,
+        };
+        let bbbv = match &self.stats {
+            Some(stats) => Some(stats.bbbv),
+            None => self
+                .dinomite
+                .to_board()
+                .map(|board| analysis::analyze(&board, self.dinomite.topology()).bbbv),
+        };
+        GameResult {
+            outcome,
+            millis: self.gamestate.elapsed_millis,
+            width: self.dinomite.get_width(),
+            height: self.dinomite.get_height(),
+            num_dinos: self.dinomite.get_num_dinos(),
+            seed: self.dinomite.seed(),
+            clicks: self.replay.events.len(),
+            flags: self.dinomite.get_num_flags(),
+            bbbv,
+            player: self.options.player_name.clone(),
+        }
+    }
+    // Which daily board this is and whether it has been cleared before.
+    fn daily_lines(&self) -> Vec<String> {
+        let Some(daily) = &self.options.daily else {
+            return vec![];
+        };
+        let status = match self.daily_best {
+            Some(millis) => format!("Cleared: {:.1}s", millis as f64 / 1000.0),
+            None => "Not cleared yet".to_string(),
+        };
+        vec![format!("Daily: {}", daily.date), status]
+    }
+    // Remember a move on the field under the cursor for the replay.
+    fn record(&mut self, action: Move) {
+        if let Some(start) = self.gamestate.game_start
+            && !self.dinomite.is_game_over()
+        {
+            let millis = start.elapsed().as_millis() as u64;
+            self.replay
+                .record(millis, action, self.gamestate.curpos.clone());
+        }
+    }
+    // Write the current game to a new file in the data directory, returning its path, or
+    // None if there is nothing to write yet.
+    fn export_board(&self) -> Result<Option<PathBuf>> {
+        let Some(board) = self.dinomite.to_board() else {
+            return Ok(None);
+        };
+        let format = self.options.export_format;
+        let bytes = format.write(&board, &self.replay)?;
+        let dir = get_data_dir();
+        fs::create_dir_all(&dir)?;
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = dir.join(format!("board-{secs}.{}", format.extension()));
+        fs::write(&path, bytes)?;
+        info!("exported board to {}", path.display());
+        Ok(Some(path))
+    }
+    // Center symbol in a field of CELL_WIDTH columns, however wide it is on screen.
+    fn pad(symbol: &str, fill: char) -> String {
+        let free = CELL_WIDTH.saturating_sub(Span::raw(symbol).width());
+        let (left, right) = (free / 2, free - free / 2);
+        let fill = fill.to_string();
+        format!("{}{}{}", fill.repeat(left), symbol, fill.repeat(right))
+    }
+    // Move the cursor one field in direction, wrapping around the edges if the topology does.
+    fn move_cursor(&mut self, direction: topology::Direction) {
+        let cur = &self.gamestate.curpos;
+        let topology = self.dinomite.topology();
+        let (dx, dy) = topology.step(cur, direction);
+        if let Some(pos) = topology.wrap(
+            cur.0 as isize + dx,
+            cur.1 as isize + dy,
+            self.dinomite.width,
+            self.dinomite.height,
+        ) {
+            self.gamestate.curpos = pos;
+        }
+    }
+    // Terminal columns taken by the row numbers in front of each row, if shown.
+    fn label_width(&self) -> usize {
+        if self.options.labels {
+            self.dinomite.height.to_string().len() + 1
+        } else {
+            0
+        }
+    }
+    // Only the part of the board around the cursor that fits into area is drawn, leaving
+    // a line for the won/lost message and room for the edge hints and labels.
+    fn visible_fields(&self, area: Rect) -> (usize, usize) {
+        let hints = if self.dinomite.topology().wraps() {
+            2
+        } else {
+            0
+        };
+        let header = if self.options.labels { 1 } else { 0 };
+        let cols =
+            ((area.width as usize).saturating_sub(hints + self.label_width()) / CELL_WIDTH).max(1);
+        let rows = (area.height as usize)
+            .saturating_sub(1 + hints + header)
+            .max(1);
+        (cols, rows)
+    }
+    // The field drawn at the given terminal cell, following the layout of `draw`: rows
+    // are centered, after the row number and the edge hint if shown, and staggered on hex
+    // boards.
+    fn field_at(&self, column: u16, row: u16) -> Option<dinomite::Position> {
+        let area = self.board_area;
+        let topology = self.dinomite.topology();
+        let hint = if topology.wraps() { 1 } else { 0 };
+        let header = if self.options.labels { 1 } else { 0 };
+        let label = self.label_width();
+        let (visible_cols, visible_rows) = self.visible_fields(area);
+        let cols = visible_cols.min(self.dinomite.width - self.scroll.0);
+        let rows = visible_rows.min(self.dinomite.height - self.scroll.1);
+
+        let y = (row as usize).checked_sub(area.y as usize + header + hint)?;
+        if y >= rows {
+            return None;
+        }
+        let row_idx = self.scroll.1 + y;
+        let staggered = topology.shifted(row_idx) != topology.shifted(row_idx + 1);
+        let half = if staggered { CELL_WIDTH / 2 } else { 0 };
+        let width = label + cols * CELL_WIDTH + 2 * hint + half;
+        // as centered by `Paragraph`
+        let left = area.x as usize + (area.width as usize / 2).saturating_sub(width / 2);
+        let shift = if topology.shifted(row_idx) { half } else { 0 };
+        let x = (column as usize).checked_sub(left + label + hint + shift)?;
+        if x >= cols * CELL_WIDTH {
+            return None;
+        }
+        Some(dinomite::Position(self.scroll.0 + x / CELL_WIDTH, row_idx))
+    }
+    // Move the cursor to the nearest field after it (or before it) in reading order that
+    // is wanted, wrapping around at the ends of the board.
+    fn jump(&mut self, forward: bool, wanted: fn(&Self, &dinomite::Position) -> bool) {
+        let (width, height) = (self.dinomite.get_width(), self.dinomite.get_height());
+        let fields = width * height;
+        let cur = self.gamestate.curpos.1 * width + self.gamestate.curpos.0;
+        let found = (1..fields)
+            .map(|step| {
+                let idx = if forward {
+                    cur + step
+                } else {
+                    cur + fields - step
+                };
+                let idx = idx % fields;
+                dinomite::Position(idx % width, idx / width)
+            })
+            .find(|pos| wanted(self, pos));
+        if let Some(pos) = found {
+            self.gamestate.curpos = pos;
+        }
+    }
+    fn is_covered(&self, pos: &dinomite::Position) -> bool {
+        matches!(
+            self.dinomite.view(pos),
+            FieldView::Covered | FieldView::Marked
+        )
+    }
+    // An uncovered number with covered fields around that are not flagged yet.
+    fn is_frontier(&self, pos: &dinomite::Position) -> bool {
+        matches!(self.dinomite.view(pos), FieldView::Revealed(1..))
+            && self
+                .dinomite
+                .get_neighbors(pos)
+                .any(|n| self.is_covered(&n))
+    }
+    // Answer the prompt with what has been typed, closing it unless the answer is wrong.
+    fn confirm_prompt(&mut self) -> Option<Action> {
+        let (prompt, typed) = self.prompt.take()?;
+        let result = match prompt {
+            Prompt::GoTo => self.go_to(&typed),
+            Prompt::NewGame => self.new_game(&typed),
+        };
+        match result {
+            Ok(()) => Some(Action::ShowGame),
+            Err(e) => {
+                self.prompt = Some((prompt, typed));
+                Some(Action::StatusError(e))
+            }
+        }
+    }
+    // Move the cursor to the field typed into the prompt, or explain what is wrong with it.
+    fn go_to(&mut self, typed: &str) -> Result<(), String> {
+        let (width, height) = (self.dinomite.get_width(), self.dinomite.get_height());
+        let pos = typed
+            .parse::<dinomite::Position>()
+            .map_err(|e| e.to_string())?;
+        if pos.0 >= width || pos.1 >= height {
+            return Err(format!("{pos} is outside of the {width}x{height} board"));
+        }
+        self.gamestate.curpos = pos;
+        Ok(())
+    }
+    // Leave the current game for one with a random layout from a seed, or with the layout
+    // of a board in the text format, with `/` or new lines between the rows.
+    fn new_game(&mut self, typed: &str) -> Result<(), String> {
+        let seed = typed.trim().parse::<u64>().ok();
+        if seed.is_none() {
+            let board: Board = typed
+                .replace('/', "\n")
+                .parse()
+                .map_err(|e| format!("neither a seed nor a board: {e}"))?;
+            check_board_size(&board)?;
+            self.options.board = Some(board);
+        } else {
+            self.options.board = None;
+        }
+        self.options.daily = None;
+        self.start_over();
+        if let Some(seed) = seed {
+            self.dinomite.set_seed(seed);
+        }
+        Ok(())
+    }
+    // A fresh game with the current options.
+    fn start_over(&mut self) {
+        self.gamestate.reset();
+        self.dinomite = Self::new_dinomite(&mut self.options);
+        self.replay = Replay::default();
+        self.stats = None;
+        self.scroll = dinomite::Position(0, 0);
+    }
+    // The prompt over the middle of the board, showing the end of long answers.
+    fn draw_prompt(&self, frame: &mut Frame, area: Rect, prompt: Prompt, typed: &str) {
+        let width = 40.min(area.width);
+        let rect = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + area.height.saturating_sub(3) / 2,
+            width,
+            height: 3.min(area.height),
         };
-        res.to_string()
+        let text: Vec<char> = typed.replace('\n', "/").chars().collect();
+        let room = (width as usize).saturating_sub(3);
+        let shown: String = text[text.len().saturating_sub(room)..].iter().collect();
+        let input = Paragraph::new(format!("{shown}▏")).block(
+            Block::bordered()
+                .title(prompt.title())
+                .title_bottom(Line::from(prompt.example()).right_aligned()),
+        );
+        frame.render_widget(Clear, rect);
+        frame.render_widget(input, rect);
+    }
+    // Keep the cursor inside the visible part of the board, scrolling as needed.
+    fn scroll_to_cursor(&mut self, visible_cols: usize, visible_rows: usize) {
+        let cur = &self.gamestate.curpos;
+        if cur.0 < self.scroll.0 {
+            self.scroll.0 = cur.0;
+        } else if cur.0 >= self.scroll.0 + visible_cols {
+            self.scroll.0 = cur.0 + 1 - visible_cols;
+        }
+        if cur.1 < self.scroll.1 {
+            self.scroll.1 = cur.1;
+        } else if cur.1 >= self.scroll.1 + visible_rows {
+            self.scroll.1 = cur.1 + 1 - visible_rows;
+        }
     }
-    fn create_styled_row(&mut self, row_idx: usize) -> Line<'static> {
-        let spans: Vec<Span> = self.cells[row_idx]
-            .iter()
-            .enumerate()
-            .map(|(col_idx, &ch)| {
+    fn create_styled_row(&self, row_idx: usize, cols: Range<usize>) -> Line<'static> {
+        let mut spans: Vec<Span> = cols
+            .map(|col_idx| {
+                let pos = dinomite::Position(col_idx, row_idx);
+                let symbol = self.dinomite.symbol(&pos, self.options.charset);
+                let ch = Self::align(&symbol);
                 if !self.dinomite.is_game_over()
                     && self.gamestate.curpos.0 == col_idx
                     && self.gamestate.curpos.1 == row_idx
                 {
                     Span::styled(
-                        format!("{:*^3}", Self::align(ch)),
+                        Self::pad(ch, '*'),
                         Style::default()
                             .fg(Color::Red)
                             .add_modifier(Modifier::BOLD)
                             .add_modifier(Modifier::UNDERLINED),
                     )
+                } else if let FieldView::Revealed(count @ 1..) = self.dinomite.view(&pos) {
+                    Span::styled(
+                        Self::pad(ch, ' '),
+                        Style::default().fg(self.options.theme.number(count)),
+                    )
                 } else {
-                    Span::raw(format!("{: ^3}", Self::align(ch)))
+                    Span::raw(Self::pad(ch, ' '))
                 }
             })
             .collect();
 
+        // staggered rows (hex boards) are padded on alternating sides by half a field
+        let topology = self.dinomite.topology();
+        if topology.shifted(row_idx) != topology.shifted(row_idx + 1) {
+            let half = " ".repeat(CELL_WIDTH / 2);
+            if topology.shifted(row_idx) {
+                spans.insert(0, Span::raw(half));
+            } else {
+                spans.push(Span::raw(half));
+            }
+        }
         Line::from(spans)
     }
 }
@@ -118,11 +681,66 @@ impl Component for Game {
         Ok(())
     }
 
+    fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        let Some((prompt, typed)) = &mut self.prompt else {
+            return Ok(None);
+        };
+        match key.code {
+            KeyCode::Char(c)
+                if !key.modifiers.intersects(!KeyModifiers::SHIFT)
+                    && typed.len() < prompt.max_len() =>
+            {
+                typed.push(c);
+            }
+            KeyCode::Backspace => {
+                typed.pop();
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+    fn handle_paste(&mut self, text: String) -> Result<Option<Action>> {
+        let Some((prompt, typed)) = &mut self.prompt else {
+            return Ok(None);
+        };
+        // a field fits on one line, boards keep their rows
+        let text = match prompt {
+            Prompt::GoTo => text.replace(['\r', '\n'], " "),
+            Prompt::NewGame => text.replace("\r\n", "\n"),
+        };
+        typed.extend(
+            text.chars()
+                .take(prompt.max_len().saturating_sub(typed.len())),
+        );
+        Ok(None)
+    }
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<Option<Action>> {
+        // clicks only reach the board while nothing is in front of it
+        if self.covered || self.prompt.is_some() {
+            return Ok(None);
+        }
+        let action = match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => Action::Look,
+            MouseEventKind::Down(MouseButton::Right) => Action::Flag,
+            MouseEventKind::Down(MouseButton::Middle) => Action::Chord,
+            _ => return Ok(None),
+        };
+        let Some(pos) = self.field_at(mouse.column, mouse.row) else {
+            return Ok(None);
+        };
+        self.gamestate.curpos = pos;
+        Ok(Some(action))
+    }
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
         self.config = config;
         Ok(())
     }
+    fn changes_on_tick(&self) -> bool {
+        // the clock counts up until the game is over
+        self.gamestate.game_start.is_some() && !self.dinomite.is_game_over()
+    }
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        let was_over = self.dinomite.is_game_over();
         match action {
             Action::Tick => {
                 // add any logic here that should run on every tick
@@ -133,54 +751,130 @@ impl Component for Game {
             Action::Flag => {
                 let pos = self.gamestate.curpos.clone(); //dinomite::Position(self.gamestate.curp, self.gamestate.cur_y);
                 if self.gamestate.game_start.is_some() {
+                    self.record(Move::Flag);
                     self.dinomite.toggle_flag(&pos); //todo something is not right w/ positions...
                 }
             }
+            Action::Unflag => {
+                let pos = self.gamestate.curpos.clone();
+                if self.gamestate.game_start.is_some() {
+                    self.record(Move::Unflag);
+                    self.dinomite.remove_flag(&pos);
+                }
+            }
             Action::Look => {
                 let pos = self.gamestate.curpos.clone(); //dinomite::Position(self.gamestate.cur_y, self.gamestate.cur_x);
                 if self.gamestate.game_start.is_some() {
+                    self.record(Move::Open);
                     self.dinomite.check_position(&pos);
                 }
                 //println!("{:?} -  {:?}", self.dinomite.is_game_over(), &pos)
             }
-            Action::MoveDown => {
-                if self.gamestate.curpos.1 <= (self.dinomite.height - 2) {
-                    self.gamestate.curpos.1 += 1;
+            Action::Chord => {
+                let pos = self.gamestate.curpos.clone();
+                if self.gamestate.game_start.is_some() {
+                    self.record(Move::Chord);
+                    self.dinomite.chord(&pos);
                 }
             }
-            Action::MoveUp => {
-                if self.gamestate.curpos.1 >= 1 {
-                    self.gamestate.curpos.1 -= 1;
-                }
+            Action::MoveDown => self.move_cursor(topology::Direction::Down),
+            Action::MoveUp => self.move_cursor(topology::Direction::Up),
+            Action::MoveLeft => self.move_cursor(topology::Direction::Left),
+            Action::MoveRight => self.move_cursor(topology::Direction::Right),
+            Action::MoveUpLeft => self.move_cursor(topology::Direction::UpLeft),
+            Action::MoveUpRight => self.move_cursor(topology::Direction::UpRight),
+            Action::MoveDownLeft => self.move_cursor(topology::Direction::DownLeft),
+            Action::MoveDownRight => self.move_cursor(topology::Direction::DownRight),
+            Action::MoveTo(x, y)
+                if x < self.dinomite.get_width() && y < self.dinomite.get_height() =>
+            {
+                self.gamestate.curpos = dinomite::Position(x, y);
             }
-            Action::MoveLeft => {
-                if self.gamestate.curpos.0 >= 1 {
-                    self.gamestate.curpos.0 -= 1;
-                }
+            Action::MoveRowStart => self.gamestate.curpos.0 = 0,
+            Action::MoveRowEnd => self.gamestate.curpos.0 = self.dinomite.get_width() - 1,
+            Action::MoveTop => self.gamestate.curpos.1 = 0,
+            Action::MoveBottom => self.gamestate.curpos.1 = self.dinomite.get_height() - 1,
+            Action::MoveToColumn(x) => {
+                self.gamestate.curpos.0 = x.min(self.dinomite.get_width() - 1);
             }
-            Action::MoveRight => {
-                if self.gamestate.curpos.0 <= (self.dinomite.width - 2) {
-                    self.gamestate.curpos.0 += 1;
-                }
+            Action::MoveToRow(y) => {
+                self.gamestate.curpos.1 = y.min(self.dinomite.get_height() - 1);
+            }
+            Action::MoveNextCovered => self.jump(true, Self::is_covered),
+            Action::MovePreviousCovered => self.jump(false, Self::is_covered),
+            Action::MoveNextFrontier => self.jump(true, Self::is_frontier),
+            Action::MovePreviousFrontier => self.jump(false, Self::is_frontier),
+            Action::GoTo => self.prompt = Some((Prompt::GoTo, String::new())),
+            Action::NewGame => self.prompt = Some((Prompt::NewGame, String::new())),
+            Action::Confirm if self.prompt.is_some() => return Ok(self.confirm_prompt()),
+            Action::ShowGame => {
+                self.prompt = None;
+                self.covered = false;
+            }
+            Action::ShowStats | Action::ShowLog => self.covered = true,
+            Action::Quit => {
+                // hand the result to the app before it exits
+                self.gamestate.update_timer();
+                return Ok(Some(Action::GameResult(self.result())));
             }
             Action::StartGame => {
                 self.gamestate.start_game();
             }
-            Action::RestartGame => {
-                if self.dinomite.is_game_over() {
-                    // allow restart
-                    self.gamestate.reset();
-                    self.dinomite = Dinomite::new(self.width, self.height, self.num_dinos);
+            Action::ExportBoard => {
+                // other tools only know rectangular boards counting all eight neighbors
+                let format = self.options.export_format;
+                if format != BoardFormat::Text
+                    && (self.options.topology != TopologyKind::Rectangular
+                        || self.options.neighborhood != Neighborhood::King)
+                {
+                    return Ok(Some(Action::Warning(format!(
+                        "{} only describes rectangular boards with eight neighbors, export this one as text",
+                        format.extension().to_uppercase()
+                    ))));
                 }
+                return Ok(Some(match self.export_board() {
+                    Ok(Some(path)) => Action::Status(format!("exported to {}", path.display())),
+                    Ok(None) => Action::Warning(
+                        "nothing to export, no field has been uncovered yet".to_string(),
+                    ),
+                    Err(e) => Action::Error(format!("could not export board: {e}")),
+                }));
             }
+            Action::RestartGame if self.dinomite.is_game_over() => self.start_over(),
 
             _ => {}
         }
         self.gamestate.update_timer();
+        let mut finished = None;
+        if !was_over && self.dinomite.is_game_over() {
+            finished = self.gamestate.game_start.map(|_| {
+                Action::GameFinished(GameRecord {
+                    preset: self.options.preset(),
+                    won: self.dinomite.is_won(),
+                    millis: self.gamestate.elapsed_millis,
+                    daily: self.options.daily.as_ref().map(|d| d.date.clone()),
+                })
+            });
+            if let Some(Action::GameFinished(record)) = &finished
+                && record.daily.is_some()
+                && record.won
+            {
+                let best = self
+                    .daily_best
+                    .map_or(record.millis, |b| b.min(record.millis));
+                self.daily_best = Some(best);
+            }
+        }
         if self.dinomite.is_game_over() {
             self.gamestate.is_game_over = true;
+            if self.stats.is_none() {
+                self.stats = self
+                    .dinomite
+                    .to_board()
+                    .map(|board| analysis::analyze(&board, self.dinomite.topology()));
+            }
         }
-        Ok(None)
+        Ok(finished)
     }
 
     fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
@@ -205,18 +899,52 @@ impl Component for Game {
         // Render the block
         frame.render_widget(block, layout[1]);
 
-        // Convert grid to displayable string with spaces between characters
-        self.cells = self
-            .dinomite
-            .to_string()
-            .lines()
-            .map(|line| line.chars().collect())
-            .collect();
+        let wraps = self.dinomite.topology().wraps();
+        let (visible_cols, visible_rows) = self.visible_fields(inner_area);
+        self.board_area = inner_area;
+        self.scroll_to_cursor(visible_cols, visible_rows);
+        let cols = self.scroll.0..(self.scroll.0 + visible_cols).min(self.dinomite.width);
+        let rows = self.scroll.1..(self.scroll.1 + visible_rows).min(self.dinomite.height);
 
         // Create styled lines for each row
-        let mut text: Vec<Line> = (0..self.cells.len())
-            .map(|row_idx| self.create_styled_row(row_idx))
+        let mut text: Vec<Line> = rows
+            .clone()
+            .map(|row_idx| self.create_styled_row(row_idx, cols.clone()))
             .collect();
+        let hint = Style::default().fg(self.options.theme.hint());
+        // lines above and below the rows start after the row numbers
+        let label_width = self.label_width();
+        let margin = " ".repeat(label_width);
+        if wraps {
+            // arrows along the edges show that leaving the board enters it on the other side
+            for line in text.iter_mut() {
+                line.spans.insert(0, Span::styled("◂", hint));
+                line.spans.push(Span::styled("▸", hint));
+            }
+            let edge = |arrow: &str| {
+                Line::styled(
+                    format!("{: ^width$}", arrow, width = CELL_WIDTH).repeat(cols.len()),
+                    hint,
+                )
+            };
+            text.insert(0, edge("▴"));
+            text.push(edge("▾"));
+            for line in [0, text.len() - 1] {
+                text[line].spans.insert(0, Span::raw(margin.clone()));
+            }
+        }
+        if self.options.labels {
+            let rows_start = if wraps { 1 } else { 0 };
+            for (line, row_idx) in text[rows_start..].iter_mut().zip(rows) {
+                let number = format!("{:>width$} ", row_idx + 1, width = label_width - 1);
+                line.spans.insert(0, Span::styled(number, hint));
+            }
+            let header: String = cols
+                .clone()
+                .map(|col_idx| Self::pad(&dinomite::column_label(col_idx), ' '))
+                .collect();
+            text.insert(0, Line::styled(format!("{margin}{header}"), hint));
+        }
         // add won/lost message to bottom
         if self.dinomite.is_won() {
             //let won_message = won_message();
@@ -228,19 +956,21 @@ impl Component for Game {
 
         //
         let mut timer_text = if self.gamestate.game_start.is_some() {
-            format!("Time: {}s", self.gamestate.elapsed_seconds)
+            format!("Time: {}s", self.gamestate.elapsed_millis / 1000)
         } else {
-            "Controls:\nstart: 's'\nquit: 'q'\nflag: <space>\nuncover: <enter>".to_string()
+            "Controls:\nstart: 's'\nquit: 'q'\nflag: <space>\nunflag: 'F'\nuncover: <enter>\nchord: 'c'"
+                .to_string()
         };
 
-        if self.dinomite.is_game_over() && self.dinomite.is_won() {
-            timer_text.push_str("\n\n😎 YOU WON!!! 😎");
-        }
-        if self.dinomite.is_game_over() && !self.dinomite.is_won() {
-            timer_text.push_str("\n\n💀 GAME OVER 💀");
+        match self.dinomite.status() {
+            GameStatus::Won => timer_text.push_str("\n\n😎 YOU WON!!! 😎"),
+            GameStatus::Lost { .. } => timer_text.push_str("\n\n💀 GAME OVER 💀"),
+            GameStatus::NotStarted | GameStatus::Playing => {}
         }
         if self.dinomite.is_game_over() {
-            timer_text.push_str("\n\nPress 'r' to reset\n\nPress 'q' to quit");
+            timer_text.push_str(
+                "\n\nPress 'r' to reset\n\nPress 't' for statistics\n\nPress 'q' to quit",
+            );
         }
         let timer = Paragraph::new(timer_text)
             .alignment(Alignment::Center)
@@ -250,12 +980,40 @@ impl Component for Game {
         //
 
         let info_text = if self.gamestate.game_start.is_some() {
-            [
+            let cur = &self.gamestate.curpos;
+            let mut info = vec![
                 format!("🦖: {}", self.dinomite.get_num_dinos()),
+                format!("🚩: {}", self.dinomite.get_num_flags()),
+                format!("Remaining: {}", self.dinomite.get_remaining_dinos()),
+                format!("Cursor: {cur} ({}, {})", cur.0, cur.1),
                 format!("Width: {}", self.dinomite.get_width()),
                 format!("Height: {}", self.dinomite.get_height()),
-            ]
-            .join("\n")
+                format!("Board: {}", self.options.topology),
+                // hex boards always count the six fields around
+                match self.options.topology {
+                    TopologyKind::Hex => "Rule: hex".to_string(),
+                    _ => format!("Rule: {}", self.options.neighborhood),
+                },
+                format!("Per field: {}", self.dinomite.get_dinos_per_field()),
+            ];
+            info.extend(self.daily_lines());
+            if let Some(stats) = &self.stats {
+                let clicks = self.replay.events.len();
+                info.push(String::new());
+                info.push(format!("3BV: {}", stats.bbbv));
+                info.push(format!("Openings: {}", stats.openings));
+                info.push(format!("Islands: {}", stats.islands));
+                info.push(format!("Clicks: {clicks}"));
+                if self.dinomite.is_won() {
+                    let millis = self.replay.duration_millis();
+                    info.push(format!("3BV/s: {:.2}", stats.bbbv_per_second(millis)));
+                    info.push(format!(
+                        "Efficiency: {:.0}%",
+                        stats.efficiency(clicks) * 100.0
+                    ));
+                }
+            }
+            info.join("\n")
         } else {
             [
                 "Not started".to_string(),
@@ -263,6 +1021,9 @@ impl Component for Game {
                 format!("Width: {}", self.dinomite.get_width()),
                 format!("Height: {}", self.dinomite.get_height()),
             ]
+            .into_iter()
+            .chain(self.daily_lines())
+            .collect::<Vec<_>>()
             .join("\n")
         };
         let info = Paragraph::new(info_text)
@@ -275,28 +1036,317 @@ impl Component for Game {
         if self.gamestate.game_start.is_some() {
             frame.render_widget(paragraph, inner_area);
         }
+        if let Some((prompt, typed)) = &self.prompt {
+            self.draw_prompt(frame, inner_area, *prompt, typed);
+        }
 
         Ok(())
     }
 }
 
 fn won_message() -> Span<'static> {
-    let won_message = Span::styled(
+    Span::styled(
         format!("{: ^3}", "\n😎 YOU WON!!! 😎"),
         Style::default()
             .fg(Color::Green)
             .add_modifier(Modifier::BOLD)
             .add_modifier(Modifier::RAPID_BLINK),
-    );
-    won_message
+    )
 }
 fn lost_message() -> Span<'static> {
-    let lost_message = Span::styled(
+    Span::styled(
         format!("{: ^3}", "💀 GAME OVER 💀"),
         Style::default()
             .fg(Color::Red)
             .add_modifier(Modifier::BOLD)
             .add_modifier(Modifier::SLOW_BLINK),
-    );
-    lost_message
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tui::Event;
+
+    fn game(board: &str) -> Game {
+        Game::new(GameOptions {
+            board: Some(board.parse().unwrap()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_export_needs_a_plain_board() {
+        for (topology, neighborhood) in [
+            (TopologyKind::Torus, Neighborhood::King),
+            (TopologyKind::Hex, Neighborhood::King),
+            (TopologyKind::Rectangular, Neighborhood::Knight),
+        ] {
+            let mut game = Game::new(GameOptions {
+                board: Some(".....\n.....\n.....\n.....\n....*".parse().unwrap()),
+                topology,
+                neighborhood,
+                export_format: BoardFormat::Mbf,
+                ..Default::default()
+            });
+            let Some(Action::Warning(message)) = game.update(Action::ExportBoard).unwrap() else {
+                panic!("exported a {topology:?} {neighborhood:?} board");
+            };
+            assert!(message.starts_with("MBF only describes rectangular boards"));
+        }
+    }
+
+    #[test]
+    fn test_result_on_quit() {
+        let mut game = game(".....\n.....\n.....\n.....\n....*");
+        let Some(Action::GameResult(result)) = game.update(Action::Quit).unwrap() else {
+            panic!("no result");
+        };
+        assert_eq!(result.outcome, Outcome::Quit);
+        assert_eq!(result.clicks, 0);
+        assert_eq!(result.bbbv, Some(1));
+
+        game.update(Action::StartGame).unwrap();
+        game.update(Action::Flag).unwrap();
+        game.update(Action::Flag).unwrap();
+        let finished = game.update(Action::Look).unwrap();
+        assert!(matches!(
+            finished,
+            Some(Action::GameFinished(GameRecord { won: true, .. }))
+        ));
+        let Some(Action::GameResult(result)) = game.update(Action::Quit).unwrap() else {
+            panic!("no result");
+        };
+        assert_eq!(result.outcome, Outcome::Won);
+        assert_eq!(result.outcome.exit_code(), 0);
+        assert_eq!((result.width, result.height, result.num_dinos), (5, 5, 1));
+        assert_eq!(result.clicks, 3);
+        assert_eq!(result.flags, 0);
+        assert_eq!(result.seed, None);
+    }
+
+    #[test]
+    fn test_restart_imported_game() {
+        let board = "*....\n.....\n.....\n.....\n....*\n\no####\n#####\n#####\n#####\n#####";
+        let mut game = game(board);
+        assert!(matches!(game.dinomite.status(), GameStatus::Lost { .. }));
+        game.update(Action::RestartGame).unwrap();
+        assert_eq!(*game.dinomite.status(), GameStatus::NotStarted);
+        assert_eq!(
+            game.dinomite.view(&dinomite::Position(0, 0)),
+            FieldView::Covered
+        );
+        assert_eq!(game.dinomite.get_num_dinos(), 2);
+    }
+
+    #[test]
+    fn test_mouse() {
+        let mut game = game(".....\n.....\n.....\n.....\n....*");
+        game.update(Action::StartGame).unwrap();
+        let mut terminal = Terminal::new(backend::TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| game.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        // the board is centered in the right 80 columns, inside the border
+        let area = game.board_area;
+        let left = area.x + area.width / 2 - 10;
+        assert_eq!(game.field_at(left, area.y), Some(dinomite::Position(0, 0)));
+        assert_eq!(
+            game.field_at(left + 4 * 3 + 3, area.y + 2),
+            Some(dinomite::Position(3, 2))
+        );
+        assert_eq!(game.field_at(left - 1, area.y), None);
+        assert_eq!(game.field_at(left + 20, area.y), None);
+        assert_eq!(game.field_at(left, area.y + 5), None);
+
+        let click = |column, row, button| MouseEvent {
+            kind: MouseEventKind::Down(button),
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        let action = game
+            .handle_mouse_event(click(left + 4, area.y + 1, MouseButton::Right))
+            .unwrap();
+        assert_eq!(action, Some(Action::Flag));
+        assert_eq!(game.gamestate.curpos, dinomite::Position(1, 1));
+
+        // nothing happens to the board behind the statistics, the log or a prompt
+        for show in [Action::ShowStats, Action::ShowLog, Action::GoTo] {
+            game.update(show).unwrap();
+            let action = game
+                .handle_mouse_event(click(left, area.y, MouseButton::Left))
+                .unwrap();
+            assert_eq!(action, None);
+            assert_eq!(game.gamestate.curpos, dinomite::Position(1, 1));
+            game.update(Action::ShowGame).unwrap();
+        }
+        let action = game
+            .handle_mouse_event(click(left, area.y, MouseButton::Left))
+            .unwrap();
+        assert_eq!(action, Some(Action::Look));
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut game = Game::new(GameOptions {
+            board: Some(".....\n.....\n.....\n.....\n....*".parse().unwrap()),
+            labels: true,
+            ..Default::default()
+        });
+        game.update(Action::StartGame).unwrap();
+        let mut terminal = Terminal::new(backend::TestBackend::new(100, 20)).unwrap();
+        terminal
+            .draw(|frame| game.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        // the row numbers take two columns in front of the fields
+        let area = game.board_area;
+        let left = area.x + area.width / 2 - 11;
+        let buffer = terminal.backend().buffer();
+        let header: String = (left..left + 22)
+            .map(|x| buffer[(x, area.y)].symbol())
+            .collect();
+        assert_eq!(header, "   A   B   C   D   E  ");
+        assert_eq!(buffer[(left, area.y + 5)].symbol(), "5");
+        assert_eq!(
+            game.field_at(left + 2, area.y + 1),
+            Some(dinomite::Position(0, 0))
+        );
+        assert_eq!(game.field_at(left + 1, area.y + 1), None);
+        assert_eq!(game.field_at(left + 2, area.y), None);
+    }
+
+    #[test]
+    fn test_go_to() {
+        let mut game = game(".....\n.....\n.....\n.....\n....*");
+        game.update(Action::StartGame).unwrap();
+        let mut type_field = |text: &str| {
+            game.update(Action::GoTo).unwrap();
+            for c in text.chars() {
+                let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+                game.handle_key_event(key).unwrap();
+            }
+            let action = game.update(Action::Confirm).unwrap();
+            (action, game.gamestate.curpos.clone())
+        };
+        assert_eq!(
+            type_field("d2"),
+            (Some(Action::ShowGame), dinomite::Position(3, 1))
+        );
+        assert_eq!(
+            type_field("4 0"),
+            (Some(Action::ShowGame), dinomite::Position(4, 0))
+        );
+        let (action, pos) = type_field("F1");
+        assert_eq!(
+            action,
+            Some(Action::StatusError(
+                "F1 is outside of the 5x5 board".to_string()
+            ))
+        );
+        assert_eq!(pos, dinomite::Position(4, 0));
+        // the prompt stays open to correct the field
+        assert_eq!(game.prompt, Some((Prompt::GoTo, "F1".to_string())));
+        game.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(game.prompt, Some((Prompt::GoTo, "F".to_string())));
+        game.update(Action::ShowGame).unwrap();
+        assert_eq!(game.prompt, None);
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut game = game(".....\n.....\n.....\n.....\n....*");
+        // pasting only goes into an open prompt
+        game.handle_events(Some(Event::Paste("c3".into()))).unwrap();
+        assert_eq!(game.prompt, None);
+
+        game.update(Action::GoTo).unwrap();
+        game.handle_events(Some(Event::Paste("2\n4".into())))
+            .unwrap();
+        assert_eq!(
+            game.update(Action::Confirm).unwrap(),
+            Some(Action::ShowGame)
+        );
+        assert_eq!(game.gamestate.curpos, dinomite::Position(2, 4));
+
+        game.update(Action::NewGame).unwrap();
+        game.handle_events(Some(Event::Paste("*....\r\n.....\r\n".into())))
+            .unwrap();
+        assert!(matches!(
+            game.update(Action::Confirm).unwrap(),
+            Some(Action::StatusError(_))
+        ));
+        game.update(Action::ShowGame).unwrap();
+
+        let board = "**.....\n.......\n.......\n.......\n......*";
+        game.update(Action::NewGame).unwrap();
+        game.handle_events(Some(Event::Paste(board.into())))
+            .unwrap();
+        assert_eq!(
+            game.update(Action::Confirm).unwrap(),
+            Some(Action::ShowGame)
+        );
+        assert_eq!(game.dinomite.get_width(), 7);
+        assert_eq!(game.dinomite.get_num_dinos(), 3);
+
+        game.update(Action::NewGame).unwrap();
+        game.handle_events(Some(Event::Paste(" 42 ".into())))
+            .unwrap();
+        game.update(Action::Confirm).unwrap();
+        assert!(game.options.board.is_none());
+        game.update(Action::StartGame).unwrap();
+        game.update(Action::Look).unwrap();
+        assert_eq!(game.dinomite.seed(), Some(42));
+    }
+
+    #[test]
+    fn test_motions() {
+        let mut game = game("*....\n.....\n.....\n....*\n.....");
+        game.update(Action::StartGame).unwrap();
+        let mut at = |action| {
+            game.update(action).unwrap();
+            let pos = &game.gamestate.curpos;
+            (pos.0, pos.1)
+        };
+        assert_eq!(at(Action::MoveRowEnd), (4, 0));
+        assert_eq!(at(Action::MoveBottom), (4, 4));
+        assert_eq!(at(Action::MoveRowStart), (0, 4));
+        assert_eq!(at(Action::MoveTop), (0, 0));
+        assert_eq!(at(Action::MoveToColumn(2)), (2, 0));
+        assert_eq!(at(Action::MoveToRow(10)), (2, 4));
+        assert_eq!(at(Action::MoveNextCovered), (3, 4));
+        assert_eq!(at(Action::MovePreviousCovered), (2, 4));
+
+        // uncovering the middle leaves the dinos and the corner behind one covered
+        at(Action::MoveTo(2, 1));
+        at(Action::Look);
+        assert_eq!(at(Action::MoveNextCovered), (4, 3));
+        assert_eq!(at(Action::MoveNextCovered), (4, 4));
+        assert_eq!(at(Action::MoveNextCovered), (0, 0));
+        assert_eq!(at(Action::MoveNextFrontier), (1, 0));
+        assert_eq!(at(Action::MovePreviousFrontier), (3, 4));
+        assert_eq!(at(Action::MovePreviousFrontier), (3, 3));
+    }
+
+    #[test]
+    fn test_preset() {
+        let options = |width, height, num_dinos| GameOptions {
+            width,
+            height,
+            num_dinos,
+            ..Default::default()
+        };
+        assert_eq!(options(9, 9, 10).preset(), "beginner");
+        assert_eq!(options(30, 16, 99).preset(), "expert");
+        assert_eq!(
+            GameOptions {
+                topology: TopologyKind::Torus,
+                dinos_per_field: 2,
+                ..options(12, 10, 20)
+            }
+            .preset(),
+            "12x10/20 torus x2"
+        );
+    }
 }
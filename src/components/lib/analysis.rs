@@ -0,0 +1,194 @@
+use crate::components::lib::board::Board;
+use crate::components::lib::dinomite::Position;
+use crate::components::lib::topology::{Topology, neighbors};
+use std::collections::VecDeque;
+
+/// How hard a layout is to clear, independent of how it was played.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoardStats {
+    /// Bechtel's Board Benchmark Value: the fewest clicks that clear the board
+    pub bbbv: usize,
+    /// Connected areas of fields without dinos nearby, each uncovered by a single click
+    pub openings: usize,
+    /// Connected groups of numbered fields that no opening uncovers
+    pub islands: usize,
+}
+
+impl BoardStats {
+    // 3BV solved per second of play.
+    pub fn bbbv_per_second(&self, millis: u64) -> f64 {
+        if millis == 0 {
+            return 0.0;
+        }
+        self.bbbv as f64 * 1000.0 / millis as f64
+    }
+    // 3BV per click, 1.0 for a perfect game; above that chording saved clicks.
+    pub fn efficiency(&self, clicks: usize) -> f64 {
+        if clicks == 0 {
+            return 0.0;
+        }
+        self.bbbv as f64 / clicks as f64
+    }
+}
+
+// The fields to click to clear board with as few clicks as possible, without chording:
+// one field of every opening, then every numbered field no opening uncovers.
+pub fn minimal_clicks(board: &Board, topology: &dyn Topology) -> (Vec<Position>, BoardStats) {
+    let (width, height) = (board.width, board.height);
+    let position = |idx: usize| Position(idx % width, idx / width);
+    let index = |pos: &Position| pos.1 * width + pos.0;
+    let safe = |idx: usize| board.dinos[idx] == 0;
+    let empty: Vec<bool> = (0..width * height)
+        .map(|idx| {
+            safe(idx) && neighbors(topology, &position(idx), width, height).all(|n| safe(index(&n)))
+        })
+        .collect();
+
+    let mut stats = BoardStats::default();
+    let mut clicks = vec![];
+    let mut uncovered = vec![false; width * height];
+    for start in 0..width * height {
+        if !empty[start] || uncovered[start] {
+            continue;
+        }
+        stats.openings += 1;
+        clicks.push(position(start));
+        uncovered[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(idx) = queue.pop_front() {
+            for n in neighbors(topology, &position(idx), width, height) {
+                let n = index(&n);
+                if !uncovered[n] {
+                    uncovered[n] = true;
+                    if empty[n] {
+                        queue.push_back(n);
+                    }
+                }
+            }
+        }
+    }
+    for start in 0..width * height {
+        if !safe(start) || uncovered[start] {
+            continue;
+        }
+        stats.islands += 1;
+        uncovered[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(idx) = queue.pop_front() {
+            clicks.push(position(idx));
+            for n in neighbors(topology, &position(idx), width, height) {
+                let n = index(&n);
+                if safe(n) && !uncovered[n] {
+                    uncovered[n] = true;
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+    stats.bbbv = clicks.len();
+    (clicks, stats)
+}
+
+pub fn analyze(board: &Board, topology: &dyn Topology) -> BoardStats {
+    minimal_clicks(board, topology).1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::lib::dinomite::{Dinomite, GameStatus};
+    use crate::components::lib::topology::{Neighborhood, Rectangular, TopologyKind};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn stats(text: &str) -> BoardStats {
+        analyze(&text.parse().unwrap(), &Rectangular::default())
+    }
+
+    #[test]
+    fn test_single_opening() {
+        let expected = BoardStats {
+            bbbv: 1,
+            openings: 1,
+            islands: 0,
+        };
+        assert_eq!(stats("*....\n.....\n.....\n.....\n....."), expected);
+    }
+
+    #[test]
+    fn test_islands() {
+        // two separate numbered fields and a pair of touching ones
+        let expected = BoardStats {
+            bbbv: 4,
+            openings: 0,
+            islands: 3,
+        };
+        assert_eq!(stats(".****\n*****\n**..*\n*****\n****."), expected);
+    }
+
+    #[test]
+    fn test_openings_and_islands() {
+        // the opening on the left uncovers the numbers next to it, but not the
+        // column of numbers between the dinos
+        let expected = BoardStats {
+            bbbv: 1 + 5,
+            openings: 1,
+            islands: 1,
+        };
+        assert_eq!(stats("..*.*\n..*.*\n..*.*\n..*.*\n..*.*"), expected);
+        let expected = BoardStats {
+            bbbv: 2,
+            openings: 2,
+            islands: 0,
+        };
+        assert_eq!(stats("..*..\n..*..\n..*..\n..*..\n..*.."), expected);
+    }
+
+    #[test]
+    fn test_efficiency() {
+        let stats = BoardStats {
+            bbbv: 30,
+            openings: 3,
+            islands: 4,
+        };
+        assert_eq!(stats.bbbv_per_second(15_000), 2.0);
+        assert_eq!(stats.efficiency(40), 0.75);
+        assert_eq!(stats.efficiency(0), 0.0);
+    }
+
+    #[test]
+    fn test_minimal_clicks_win() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let variants = [
+            (TopologyKind::Rectangular, Neighborhood::King),
+            (TopologyKind::Rectangular, Neighborhood::Cross),
+            (TopologyKind::Rectangular, Neighborhood::Knight),
+            (TopologyKind::Torus, Neighborhood::King),
+            (TopologyKind::Torus, Neighborhood::Radius2),
+            (TopologyKind::Hex, Neighborhood::King),
+        ];
+        for (kind, neighborhood) in variants {
+            for _ in 0..20 {
+                let (width, height) = (rng.random_range(5..12), rng.random_range(5..12));
+                let dinos = (0..width * height)
+                    .map(|_| rng.random_bool(0.2) as u8)
+                    .collect();
+                let board = Board {
+                    width,
+                    height,
+                    dinos,
+                    state: None,
+                };
+                let topology = kind.topology(neighborhood);
+                let (clicks, stats) = minimal_clicks(&board, topology.as_ref());
+                let mut dinomite = Dinomite::from_board(&board, topology);
+                for (i, pos) in clicks.iter().enumerate() {
+                    assert_ne!(dinomite.status(), &GameStatus::Won, "click {i} is useless");
+                    dinomite.check_position(pos);
+                }
+                if stats.bbbv > 0 {
+                    assert_eq!(dinomite.status(), &GameStatus::Won);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use color_eyre::{Result, eyre::eyre};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A board layout, optionally with the state of a game played on it, in plain text.
+///
+/// The layout is a grid with one character per field: `.` for a safe field, `*` for a
+/// dino and `2`-`9` for a field hiding several dinos. An optional second grid, separated
+/// by an empty line, holds the game state: `#` for a covered field, `o` for an uncovered
+/// one and `1`-`9` for the number of flags on a covered field. Lines starting with `;`
+/// are comments.
+///
+/// ```text
+/// ; 5x5, 3 dinos
+/// *....
+/// ..*..
+/// .....
+/// ....*
+/// .....
+///
+/// 1####
+/// #####
+/// #####
+/// oo###
+/// ooo##
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    pub width: usize,
+    pub height: usize,
+    // dinos per field, row by row
+    pub dinos: Vec<u8>,
+    // state per field, row by row; None for a fresh game
+    pub state: Option<Vec<FieldState>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldState {
+    #[default]
+    Covered,
+    Uncovered,
+    Flagged(u8),
+}
+
+impl Board {
+    pub fn num_dinos(&self) -> usize {
+        self.dinos.iter().map(|&d| d as usize).sum()
+    }
+    // Most dinos hidden in a single field, at least 1.
+    pub fn dinos_per_field(&self) -> u8 {
+        self.dinos.iter().copied().max().unwrap_or(0).max(1)
+    }
+}
+
+// Split lines into grids separated by empty lines, skipping comments.
+fn grids(s: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut grids = vec![];
+    let mut current = vec![];
+    for (number, line) in s.lines().enumerate() {
+        let line = line.trim_end();
+        if line.starts_with(';') {
+            continue;
+        }
+        if line.is_empty() {
+            if !current.is_empty() {
+                grids.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        current.push((number + 1, line));
+    }
+    if !current.is_empty() {
+        grids.push(current);
+    }
+    grids
+}
+
+// Parse a grid of width x height fields, one character each.
+fn parse_grid<T>(
+    grid: &[(usize, &str)],
+    width: usize,
+    field: impl Fn(char) -> Option<T>,
+) -> Result<Vec<T>> {
+    let mut fields = Vec::with_capacity(width * grid.len());
+    for (number, line) in grid {
+        let len = line.chars().count();
+        if len != width {
+            return Err(eyre!(
+                "line {number}: expected {width} fields like the first row, found {len}"
+            ));
+        }
+        for (column, c) in line.chars().enumerate() {
+            let parsed = field(c)
+                .ok_or_else(|| eyre!("line {number}, column {}: unexpected '{c}'", column + 1))?;
+            fields.push(parsed);
+        }
+    }
+    Ok(fields)
+}
+
+impl FromStr for Board {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let grids = grids(s);
+        let (layout, state) = match grids.as_slice() {
+            [layout] => (layout, None),
+            [layout, state] => (layout, Some(state)),
+            [] => return Err(eyre!("the board is empty")),
+            _ => return Err(eyre!("expected a layout and at most one state grid")),
+        };
+        let width = layout[0].1.chars().count();
+        let height = layout.len();
+        let dinos = parse_grid(layout, width, |c| match c {
+            '.' => Some(0),
+            '*' => Some(1),
+            '2'..='9' => c.to_digit(10).map(|d| d as u8),
+            _ => None,
+        })?;
+        let state = match state {
+            Some(state) => {
+                if state.len() != height {
+                    return Err(eyre!(
+                        "line {}: the state has {} rows, the layout {height}",
+                        state[0].0,
+                        state.len()
+                    ));
+                }
+                Some(parse_grid(state, width, |c| match c {
+                    '#' => Some(FieldState::Covered),
+                    'o' => Some(FieldState::Uncovered),
+                    '1'..='9' => c.to_digit(10).map(|d| FieldState::Flagged(d as u8)),
+                    _ => None,
+                })?)
+            }
+            None => None,
+        };
+        Ok(Board {
+            width,
+            height,
+            dinos,
+            state,
+        })
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "; {}x{}, {} dinos",
+            self.width,
+            self.height,
+            self.num_dinos()
+        )?;
+        for row in self.dinos.chunks(self.width) {
+            for &dinos in row {
+                match dinos {
+                    0 => write!(f, ".")?,
+                    1 => write!(f, "*")?,
+                    n => write!(f, "{n}")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        if let Some(state) = &self.state {
+            writeln!(f)?;
+            for row in state.chunks(self.width) {
+                for field in row {
+                    match field {
+                        FieldState::Covered => write!(f, "#")?,
+                        FieldState::Uncovered => write!(f, "o")?,
+                        FieldState::Flagged(n) => write!(f, "{n}")?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_layout() {
+        let board: Board = "; a comment\n*..\n.2.\n".parse().unwrap();
+        assert_eq!(board.width, 3);
+        assert_eq!(board.height, 2);
+        assert_eq!(board.dinos, vec![1, 0, 0, 0, 2, 0]);
+        assert_eq!(board.num_dinos(), 3);
+        assert_eq!(board.dinos_per_field(), 2);
+        assert_eq!(board.state, None);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "*...\n..*.\n\n1o##\n#oo#\n";
+        let board: Board = text.parse().unwrap();
+        assert_eq!(
+            board.state.as_ref().unwrap()[..3],
+            [
+                FieldState::Flagged(1),
+                FieldState::Uncovered,
+                FieldState::Covered
+            ]
+        );
+        assert_eq!(board.to_string(), format!("; 4x2, 2 dinos\n{text}"));
+        assert_eq!(board.to_string().parse::<Board>().unwrap(), board);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str| s.parse::<Board>().unwrap_err().to_string();
+        assert_eq!(err(""), "the board is empty");
+        assert_eq!(
+            err("*..\n..\n"),
+            "line 2: expected 3 fields like the first row, found 2"
+        );
+        assert_eq!(err("*.x\n"), "line 1, column 3: unexpected 'x'");
+        assert_eq!(
+            err("*..\n...\n\n###\n"),
+            "line 4: the state has 1 rows, the layout 2"
+        );
+        assert_eq!(
+            err("*.\n\n##\n\n##\n"),
+            "expected a layout and at most one state grid"
+        );
+    }
+}
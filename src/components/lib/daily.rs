@@ -0,0 +1,66 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Everybody plays the daily board on an intermediate sized board.
+pub const DAILY_WIDTH: usize = 16;
+pub const DAILY_HEIGHT: usize = 16;
+pub const DAILY_DINOS: usize = 40;
+
+/// The board of the day, the same for everybody playing on that date (in UTC).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Daily {
+    // as YYYY-MM-DD
+    pub date: String,
+    pub seed: u64,
+}
+
+impl Daily {
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::from_days(secs / 86400)
+    }
+    // The daily board for the given number of days since 1970-01-01.
+    pub fn from_days(days: u64) -> Self {
+        let (year, month, day) = civil_from_days(days);
+        Daily {
+            date: format!("{year:04}-{month:02}-{day:02}"),
+            // spread consecutive days over the whole range of seeds
+            seed: days.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+}
+
+// Convert days since 1970-01-01 into year, month and day of the Gregorian calendar,
+// following Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(Daily::from_days(0).date, "1970-01-01");
+        assert_eq!(Daily::from_days(11_016).date, "2000-02-29");
+        assert_eq!(Daily::from_days(20_088).date, "2024-12-31");
+        assert_eq!(Daily::from_days(20_744).date, "2026-10-18");
+    }
+
+    #[test]
+    fn test_seeds_differ() {
+        assert_eq!(Daily::from_days(20_744), Daily::from_days(20_744));
+        assert_ne!(Daily::from_days(20_744).seed, Daily::from_days(20_745).seed);
+    }
+}
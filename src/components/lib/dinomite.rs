@@ -1,12 +1,17 @@
+use crate::components::lib::board::{Board, FieldState};
 use crate::components::lib::dinomite::PositionResult::{
-    Clear, Dino, DinosInSurrounding, Flagged, Over,
+    Clear, Dino, DinosInSurrounding, Flagged, Over, Unchanged,
 };
-use itertools::Itertools;
+use crate::components::lib::rng::SplitMix64;
+use crate::components::lib::topology::{Rectangular, Topology, neighbors};
+use clap::ValueEnum;
+use color_eyre::eyre::eyre;
 use rand::Rng;
-use std::cmp::min;
-use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fmt::Write as _;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq)]
 pub enum PositionResult {
@@ -15,73 +20,340 @@ pub enum PositionResult {
     DinosInSurrounding(usize), //
     Dino,
     Flagged,
+    Unchanged, // nothing to do, e.g. a chord on a covered field
+}
+#[derive(Debug, Default, Hash, Clone, PartialEq, Eq)]
+pub struct Position(pub usize, pub usize);
+
+// Letters naming a column, going on after Z with AA, AB and so on.
+pub fn column_label(mut x: usize) -> String {
+    let mut label = String::new();
+    loop {
+        label.insert(0, char::from(b'A' + (x % 26) as u8));
+        if x < 26 {
+            return label;
+        }
+        x = x / 26 - 1;
+    }
+}
+
+// Fields are named by column letters and the row counted from 1, e.g. `C7` for
+// Position(2, 6).
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", column_label(self.0), self.1 + 1)
+    }
+}
+
+// Either a name such as `C7` (or `c7`) or, as in the headless protocol, `X Y` counted
+// from 0.
+impl FromStr for Position {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> color_eyre::Result<Self> {
+        let s = s.trim();
+        let invalid = || eyre!("`{s}` is not a field, try e.g. C7 or 2 6");
+        let numbers: Vec<&str> = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .collect();
+        if let [x, y] = numbers[..] {
+            return match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Ok(Position(x, y)),
+                _ => Err(invalid()),
+            };
+        }
+        let letters = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (column, row) = s.split_at(letters);
+        let row: usize = row.parse().map_err(|_| invalid())?;
+        if column.is_empty() || row == 0 {
+            return Err(invalid());
+        }
+        let column = column.chars().fold(0usize, |n, c| {
+            n.saturating_mul(26)
+                .saturating_add(c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+        });
+        Ok(Position(column - 1, row - 1))
+    }
+}
+
+/// Symbols the board is drawn with.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, strum::Display, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Charset {
+    #[default]
+    Emoji,
+    // plain characters, for terminals and fonts without emoji
+    Ascii,
+}
+
+// What the player gets to see of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldView {
+    Covered,
+    Flagged(u8),
+    // a question mark
+    Marked,
+    // uncovered, with the number of dinos nearby
+    Revealed(u16),
+    // only shown once the game is over
+    Dino(u8),
+    Exploded,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum GameStatus {
+    #[default]
+    NotStarted, // nothing has been uncovered yet
+    Playing,
+    Won,
+    Lost {
+        exploded_at: Position,
+    },
+}
+
+// State of a single field on the board, stored row by row in `Dinomite::cells`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Cell {
+    // number of dinos hidden here, at most `Dinomite::dinos_per_field`
+    dinos: u8,
+    seen: bool,
+    // number of dinos the player believes to be hidden here
+    flags: u8,
+    // number of dinos in the neighborhood, kept up to date when placing dinos
+    count: u16,
+    // marked with a question mark, without counting as a flag
+    marked: bool,
 }
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
-pub(crate) struct Position(pub(crate) usize, pub(crate) usize);
 
 pub struct Dinomite {
     pub width: usize,
     pub height: usize,
-    seen: HashSet<Position>,
-    dinos: HashSet<Position>,
-    pub flags: HashSet<Position>,
-    game_over: bool,
-    won: bool,
+    cells: Vec<Cell>,
+    num_dinos: usize,
+    // fields hiding at least one dino
+    num_dino_fields: usize,
+    // dinos still to be hidden when the first field is uncovered
+    pending_dinos: usize,
+    // how many dinos may share a single field
+    dinos_per_field: u8,
+    num_seen: usize,
+    num_flags: usize,
+    status: GameStatus,
+    // refuse to place more flags than there are dinos
+    flag_limit: bool,
+    // keep the first uncovered field and its neighbors free of dinos
+    safe_start: bool,
+    // cycle through a question mark after the flags when flagging a field
+    question_marks: bool,
+    // seed of the random layout; chosen when hiding the dinos unless set beforehand
+    seed: Option<u64>,
+    topology: Box<dyn Topology>,
 }
 impl Default for Dinomite {
     fn default() -> Self {
         Self::new(9, 8, 9)
     }
 }
-fn get_random_numbers(n: usize, max_val: usize) -> Vec<usize> {
-    let mut rng = rand::rng();
-    let mut numbers: Vec<usize> = Vec::with_capacity(n);
-    for _ in 0..n {
-        numbers.push(rng.random_range(0..max_val));
+// Choose amount distinct indices out of 0..num_fields, never picking an excluded one.
+//
+// This is a partial Fisher-Yates shuffle over the allowed indices: every step swaps a
+// uniformly chosen remaining candidate to the front, so each of the possible layouts
+// is equally likely and no retries are needed, however dense the board.
+pub(crate) fn random_fields(
+    rng: &mut SplitMix64,
+    num_fields: usize,
+    amount: usize,
+    excluded: &[usize],
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..num_fields).filter(|i| !excluded.contains(i)).collect();
+    let amount = amount.min(candidates.len());
+    for i in 0..amount {
+        let j = i + rng.below((candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
     }
-    numbers
+    candidates.truncate(amount);
+    candidates
 }
 
 impl Dinomite {
     // Create a width x height field with num_dinos hidden dinos.
     // Upon starting no fields have been opened, no flags are set.
+    // The dinos are hidden once the first field gets uncovered.
     pub fn new(width: usize, height: usize, num_dinos: usize) -> Self {
-        let num_dinos = match num_dinos {
-            n if n <= (height) * (width) => n,
-            _ => (height) * (width),
-        };
-
+        Self::with_topology(width, height, num_dinos, Box::new(Rectangular::default()))
+    }
+    // Like `new`, but with the fields connected according to topology.
+    pub fn with_topology(
+        width: usize,
+        height: usize,
+        num_dinos: usize,
+        topology: Box<dyn Topology>,
+    ) -> Self {
         Dinomite {
             width,
             height,
-            seen: HashSet::new(),
-            dinos: {
-                let mut d = HashSet::new();
-                let xp = get_random_numbers(num_dinos, width);
-                let yp = get_random_numbers(num_dinos, height);
-
-                for (x, y) in xp.iter().zip(yp) {
-                    d.insert(Position(*x, y));
-                    if d.len() == num_dinos {
-                        break;
+            cells: vec![Cell::default(); width * height],
+            num_dinos: 0,
+            num_dino_fields: 0,
+            pending_dinos: num_dinos,
+            dinos_per_field: 1,
+            num_seen: 0,
+            num_flags: 0,
+            status: GameStatus::NotStarted,
+            flag_limit: true,
+            safe_start: false,
+            question_marks: false,
+            seed: None,
+            topology,
+        }
+    }
+    // Set up the layout of board, continuing the game recorded with it if there is one.
+    pub fn from_board(board: &Board, topology: Box<dyn Topology>) -> Self {
+        let mut dinomite = Self::with_topology(board.width, board.height, 0, topology);
+        dinomite.set_dinos_per_field(board.dinos_per_field());
+        for (idx, &dinos) in board.dinos.iter().enumerate() {
+            for _ in 0..dinos {
+                dinomite.set_dino(&dinomite.position(idx));
+            }
+        }
+        let Some(state) = &board.state else {
+            return dinomite;
+        };
+        let mut exploded_at = None;
+        for (idx, field) in state.iter().enumerate() {
+            let cell = &mut dinomite.cells[idx];
+            match *field {
+                FieldState::Covered => {}
+                FieldState::Uncovered => {
+                    cell.seen = true;
+                    dinomite.num_seen += 1;
+                    if cell.dinos > 0 {
+                        exploded_at = Some(dinomite.position(idx));
                     }
                 }
-                while d.len() < num_dinos {
-                    let xp = get_random_numbers(num_dinos, width);
-                    let yp = get_random_numbers(num_dinos, height);
-
-                    for (x, y) in xp.iter().zip(yp) {
-                        d.insert(Position(*x, y));
-                        if d.len() == num_dinos {
-                            break;
-                        }
-                    }
+                FieldState::Flagged(flags) => {
+                    cell.flags = flags;
+                    dinomite.num_flags += flags as usize;
                 }
-                d
-            },
-            flags: HashSet::new(),
-            game_over: false,
-            won: false,
+            }
+        }
+        dinomite.status = match exploded_at {
+            Some(exploded_at) => GameStatus::Lost { exploded_at },
+            None if dinomite.num_seen == 0 => GameStatus::NotStarted,
+            None if dinomite.num_seen == board.width * board.height - dinomite.num_dino_fields => {
+                GameStatus::Won
+            }
+            None => GameStatus::Playing,
+        };
+        dinomite
+    }
+    // The layout and state of the current game, or None while the dinos are not hidden yet.
+    pub fn to_board(&self) -> Option<Board> {
+        if self.pending_dinos > 0 {
+            return None;
+        }
+        Some(Board {
+            width: self.width,
+            height: self.height,
+            dinos: self.cells.iter().map(|cell| cell.dinos).collect(),
+            state: Some(
+                self.cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Cell { seen: true, .. } => FieldState::Uncovered,
+                        Cell { flags: 0, .. } => FieldState::Covered,
+                        Cell { flags, .. } => FieldState::Flagged(*flags),
+                    })
+                    .collect(),
+            ),
+        })
+    }
+    // Allow or forbid placing more flags than there are dinos.
+    pub fn set_flag_limit(&mut self, flag_limit: bool) {
+        self.flag_limit = flag_limit;
+    }
+    // Make sure the first uncovered field (and its neighbors, if possible) are free of dinos.
+    pub fn set_safe_start(&mut self, safe_start: bool) {
+        self.safe_start = safe_start;
+    }
+    pub fn set_question_marks(&mut self, question_marks: bool) {
+        self.question_marks = question_marks;
+    }
+    // Derive the layout from seed, so the same seed (and first field, with a safe start)
+    // always gives the same board.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    // Seed the layout was generated from, None for fixed layouts or before the first move.
+    pub fn seed(&self) -> Option<u64> {
+        if self.pending_dinos > 0 {
+            return None;
+        }
+        self.seed
+    }
+    // Allow up to dinos_per_field dinos to share a single field.
+    pub fn set_dinos_per_field(&mut self, dinos_per_field: u8) {
+        self.dinos_per_field = dinos_per_field.max(1);
+    }
+    // Most dinos the board can hide.
+    fn capacity(&self) -> usize {
+        self.width * self.height * self.dinos_per_field as usize
+    }
+    // Hide the pending dinos, keeping them away from the first uncovered field if requested.
+    //
+    // Every field offers dinos_per_field slots and the dinos are spread over the slots,
+    // so with a single dino per field this is a plain uniform choice of fields.
+    fn place_pending_dinos(&mut self, first: &Position) {
+        let per_field = self.dinos_per_field as usize;
+        let num_slots = self.capacity();
+        debug_assert!(
+            self.pending_dinos <= num_slots,
+            "{} dinos do not fit into {num_slots} slots",
+            self.pending_dinos
+        );
+        let mut excluded = Vec::new();
+        if self.safe_start {
+            excluded.push(self.index(first));
+            excluded.extend(self.get_neighbors(first).map(|n| self.index(&n)));
+            if num_slots - excluded.len() * per_field < self.pending_dinos {
+                excluded.truncate(1);
+            }
+            if num_slots - excluded.len() * per_field < self.pending_dinos {
+                excluded.clear();
+            }
+        }
+        let excluded: Vec<usize> = excluded
+            .into_iter()
+            .flat_map(|idx| idx * per_field..(idx + 1) * per_field)
+            .collect();
+        let seed = *self.seed.get_or_insert_with(|| rand::rng().random());
+        let mut rng = SplitMix64::new(seed);
+        let slots = random_fields(&mut rng, num_slots, self.pending_dinos, &excluded);
+        self.pending_dinos = 0;
+        for slot in slots {
+            self.set_dino(&self.position(slot / per_field));
+        }
+    }
+    // Hide a dino at pos and update the counts of the surrounding fields.
+    // Does nothing if the field already holds as many dinos as allowed.
+    pub fn set_dino(&mut self, pos: &Position) {
+        let idx = self.index(pos);
+        if self.cells[idx].dinos >= self.dinos_per_field {
+            return;
+        }
+        if self.cells[idx].dinos == 0 {
+            self.num_dino_fields += 1;
+        }
+        self.cells[idx].dinos += 1;
+        self.num_dinos += 1;
+        for n in neighbors(self.topology.as_ref(), pos, self.width, self.height) {
+            self.cells[n.1 * self.width + n.0].count += 1;
         }
     }
     /*pub fn reconfigure(&mut self, height: usize, width: usize, num_dinos: usize) {
@@ -90,16 +362,23 @@ impl Dinomite {
         if num_dinos < height * width {
             tmp = Dinomite::new(height, width, num_dinos);
         }
-        self.dinos = tmp.dinos.clone();
-        self.flags = tmp.flags.clone();
-        self.seen = tmp.seen.clone();
+        self.cells = tmp.cells.clone();
         self.width = tmp.width;
         self.height = tmp.height;
-        self.won = tmp.won;
-        self.game_over = tmp.game_over;
+        self.status = tmp.status;
     }*/
     pub fn get_num_dinos(&self) -> usize {
-        self.dinos.len()
+        self.num_dinos + self.pending_dinos
+    }
+    pub fn get_dinos_per_field(&self) -> u8 {
+        self.dinos_per_field
+    }
+    pub fn get_num_flags(&self) -> usize {
+        self.num_flags
+    }
+    // Dinos minus flags; negative if more flags than dinos have been placed.
+    pub fn get_remaining_dinos(&self) -> isize {
+        self.get_num_dinos() as isize - self.num_flags as isize
     }
     pub fn get_width(&self) -> usize {
         self.width
@@ -108,100 +387,255 @@ impl Dinomite {
         self.height
     }
 
-    pub(crate) fn check_position(&mut self, pos: &Position) -> PositionResult {
-        if self.won || self.game_over {
+    fn index(&self, pos: &Position) -> usize {
+        pos.1 * self.width + pos.0
+    }
+    fn position(&self, idx: usize) -> Position {
+        Position(idx % self.width, idx / self.width)
+    }
+
+    // Uncover pos as one full move and update the game status afterwards.
+    pub fn check_position(&mut self, pos: &Position) -> PositionResult {
+        if self.is_game_over() {
             return Over;
         }
-        if self.flags.contains(pos) {
-            return Flagged;
+        if self.pending_dinos > 0 && self.cells[self.index(pos)].flags == 0 {
+            self.place_pending_dinos(pos);
         }
-        let mut surrounding = 0usize;
-
-        if self.dinos.contains(pos) {
-            self.game_over = true;
+        let result = self.reveal(pos);
+        self.update_status(pos, &result);
+        result
+    }
 
-            return Dino;
+    // Whether a chord at pos uncovers anything: the field is uncovered and surrounded by as
+    // many flags as it has dinos nearby (fields flagged several times count several times).
+    pub fn can_chord(&self, pos: &Position) -> bool {
+        let count = self.get_neighboring_dino_count(pos);
+        let flagged: usize = self
+            .get_neighbors(pos)
+            .map(|n| self.cells[self.index(&n)].flags as usize)
+            .sum();
+        self.cells[self.index(pos)].seen && count > 0 && flagged == count
+    }
+    // Uncover all unflagged neighbors of the uncovered field at pos as one full move,
+    // provided its flags are complete, see `can_chord`.
+    pub fn chord(&mut self, pos: &Position) -> PositionResult {
+        if self.is_game_over() {
+            return Over;
+        }
+        if !self.can_chord(pos) {
+            return Unchanged;
         }
-        if self.flags.contains(pos) {
+        let count = self.get_neighboring_dino_count(pos);
+        let mut result = DinosInSurrounding(count);
+        let mut exploded_at = pos.clone();
+        for n in self.get_neighbors(pos).collect::<Vec<_>>() {
+            if self.reveal(&n) == Dino && result != Dino {
+                result = Dino;
+                exploded_at = n;
+            }
+        }
+        self.update_status(&exploded_at, &result);
+        result
+    }
+
+    // Uncover pos; if it has no dinos nearby, keep uncovering the surrounding
+    // fields breadth-first until the whole opening is revealed.
+    fn reveal(&mut self, pos: &Position) -> PositionResult {
+        let idx = self.index(pos);
+        let cell = self.cells[idx];
+        if cell.flags > 0 {
             return Flagged;
         }
-        if self.seen.contains(pos) {
-            return Clear;
+        if cell.dinos > 0 {
+            return Dino;
         }
-        if self.seen.len() == self.width * self.height - self.dinos.len() - 1
-            && !self.dinos.contains(pos)
-        {
-            self.won = true;
-            self.game_over = true;
+        if cell.seen {
+            return Clear;
         }
-        for n in self.get_neighbors(pos) {
-            if self.dinos.contains(&n) {
-                surrounding += 1;
+        let mut queue = VecDeque::from([idx]);
+        self.cells[idx].seen = true;
+        self.num_seen += 1;
+        while let Some(idx) = queue.pop_front() {
+            if self.cells[idx].count != 0 {
+                continue;
             }
-        }
-        match surrounding {
-            0 => {
-                self.seen.insert(pos.clone());
-                for n in self.get_neighbors(pos) {
-                    self.check_position(&n);
+            let pos = self.position(idx);
+            for n in neighbors(self.topology.as_ref(), &pos, self.width, self.height) {
+                let n = n.1 * self.width + n.0;
+                let cell = &mut self.cells[n];
+                if cell.seen || cell.flags > 0 || cell.dinos > 0 {
+                    continue;
                 }
-                Clear
-            }
-            _ => {
-                self.seen.insert(pos.clone());
-                DinosInSurrounding(surrounding)
+                cell.seen = true;
+                self.num_seen += 1;
+                queue.push_back(n);
             }
         }
+        match self.get_neighboring_dino_count(pos) {
+            0 => Clear,
+            count => DinosInSurrounding(count),
+        }
     }
 
-    fn get_neighbors(&self, pos: &Position) -> impl Iterator<Item = Position> + use<> {
-        let neighbors = [
-            (pos.0.saturating_sub(1), pos.1),                           //left
-            (pos.0.saturating_sub(1), pos.1.saturating_sub(1)),         // top left
-            (pos.0.saturating_sub(1), min(pos.1 + 1, self.height - 1)), // bottom left
-            (pos.0, pos.1.saturating_sub(1)),                           // top
-            (pos.0, min(pos.1 + 1, self.height - 1)),                   // bottom
-            (min(pos.0 + 1, self.width - 1), pos.1),                    // right
-            (min(pos.0 + 1, self.width - 1), pos.1.saturating_sub(1)),  // top right
-            (
-                min(pos.0 + 1, self.width - 1),
-                min(pos.1 + 1, self.height - 1),
-            ), // bottom right
-        ];
-        neighbors.into_iter().unique().map(|x| Position(x.0, x.1))
+    fn update_status(&mut self, pos: &Position, result: &PositionResult) {
+        self.status = match result {
+            Dino => GameStatus::Lost {
+                exploded_at: pos.clone(),
+            },
+            Flagged | Over | Unchanged => return,
+            _ if self.num_seen == self.width * self.height - self.num_dino_fields => {
+                GameStatus::Won
+            }
+            _ => GameStatus::Playing,
+        };
+    }
+
+    pub fn topology(&self) -> &dyn Topology {
+        self.topology.as_ref()
+    }
+    pub fn get_neighbors(&self, pos: &Position) -> impl Iterator<Item = Position> + '_ {
+        neighbors(self.topology.as_ref(), pos, self.width, self.height)
     }
     fn get_neighboring_dino_count(&self, pos: &Position) -> usize {
-        let mut result = 0;
-        for n in self.get_neighbors(pos) {
-            if self.dinos.contains(&n) {
-                result += 1;
-            }
-        }
-        result
+        self.cells[self.index(pos)].count as usize
     }
-    pub(crate) fn toggle_flag(&mut self, pos: &Position) {
-        if self.game_over || self.won {
+    // Add a flag at pos. Once the field holds as many flags as dinos fit in a field, or no
+    // more flags are allowed, all flags on it are removed instead, making way for a
+    // question mark if those are used.
+    pub fn toggle_flag(&mut self, pos: &Position) {
+        if self.is_game_over() {
             return;
         }
-        if self.seen.contains(pos) {
+        let idx = self.index(pos);
+        if self.cells[idx].seen {
             return;
         }
-        if self.flags.contains(pos) {
-            self.flags.remove(pos);
+        if self.cells[idx].marked {
+            self.cells[idx].marked = false;
+            return;
+        }
+        let flags = self.cells[idx].flags;
+        if flags >= self.dinos_per_field
+            || (self.flag_limit && self.num_flags >= self.get_num_dinos())
+        {
+            self.cells[idx].flags = 0;
+            self.cells[idx].marked = self.question_marks;
+            self.num_flags -= flags as usize;
         } else {
-            if self.flags.len() == self.dinos.len() {
-                return;
-            }
+            self.cells[idx].flags += 1;
+            self.num_flags += 1;
+        }
+    }
+    // Take a single flag, or else the question mark, off the field at pos.
+    pub fn remove_flag(&mut self, pos: &Position) {
+        if self.is_game_over() {
+            return;
+        }
+        let idx = self.index(pos);
+        if self.cells[idx].flags > 0 {
+            self.cells[idx].flags -= 1;
+            self.num_flags -= 1;
+        } else {
+            self.cells[idx].marked = false;
+        }
+    }
+
+    pub fn status(&self) -> &GameStatus {
+        &self.status
+    }
+    pub fn is_game_over(&self) -> bool {
+        matches!(self.status, GameStatus::Won | GameStatus::Lost { .. })
+    }
+    pub fn is_won(&self) -> bool {
+        self.status == GameStatus::Won
+    }
 
-            self.flags.insert(pos.clone());
+    pub fn view(&self, pos: &Position) -> FieldView {
+        let cell = self.cells[self.index(pos)];
+        match &self.status {
+            _ if cell.seen => FieldView::Revealed(cell.count),
+            _ if cell.flags > 0 => FieldView::Flagged(cell.flags),
+            GameStatus::NotStarted | GameStatus::Playing if cell.marked => FieldView::Marked,
+            GameStatus::Lost { exploded_at } if exploded_at == pos => FieldView::Exploded,
+            GameStatus::Won | GameStatus::Lost { .. } if cell.dinos > 0 => {
+                FieldView::Dino(cell.dinos)
+            }
+            _ => FieldView::Covered,
         }
     }
 
-    pub(crate) fn is_game_over(&self) -> bool {
-        self.game_over
+    // Symbol shown for the field at pos in the current game state.
+    // Fields holding several dinos or flags are prefixed with how many.
+    pub(crate) fn symbol(&self, pos: &Position, charset: Charset) -> String {
+        let pick = |emoji: &'static str, ascii: &'static str| match charset {
+            Charset::Emoji => emoji,
+            Charset::Ascii => ascii,
+        };
+        let cell = self.cells[self.index(pos)];
+        let symbol = match &self.status {
+            GameStatus::Won => {
+                if cell.flags > 0 {
+                    if cell.flags == cell.dinos {
+                        pick("😼", "F")
+                    } else {
+                        pick("😨", "x") //should never happen
+                    }
+                } else if cell.dinos > 0 {
+                    return Self::multiple(cell.dinos, pick("🦖", "*"));
+                } else if cell.seen {
+                    match cell.count {
+                        0 => pick("🌠", "."),
+                        count => return count.to_string(),
+                    }
+                } else {
+                    pick("🌺", "#")
+                }
+            }
+            GameStatus::Lost { exploded_at } => {
+                if cell.flags > 0 {
+                    if cell.dinos == 0 {
+                        //"❌️"
+                        pick("😵", "x")
+                    } else {
+                        pick("😬", "F")
+                    }
+                } else if exploded_at == pos {
+                    pick("💥", "X")
+                } else if cell.dinos > 0 {
+                    return Self::multiple(cell.dinos, pick("🦖", "*"));
+                } else if cell.seen {
+                    match cell.count {
+                        0 => pick("🔲", "."),
+                        count => return count.to_string(),
+                    }
+                } else {
+                    pick("🍂", "#")
+                }
+            }
+            // game is still running
+            GameStatus::NotStarted | GameStatus::Playing => {
+                if cell.flags > 0 {
+                    return Self::multiple(cell.flags, pick("🚩", "F"));
+                } else if cell.seen {
+                    match cell.count {
+                        0 => pick("🔲", "."),
+                        count => return count.to_string(),
+                    }
+                } else if cell.marked {
+                    pick("❓", "?")
+                } else {
+                    pick("🌿", "#")
+                }
+            }
+        };
+        symbol.to_string()
     }
-    pub(crate) fn is_won(&self) -> bool {
-        self.won
+    fn multiple(amount: u8, symbol: &str) -> String {
+        match amount {
+            1 => symbol.to_string(),
+            n => format!("{n}{symbol}"),
+        }
     }
 }
 impl Display for Dinomite {
@@ -209,78 +643,7 @@ impl Display for Dinomite {
         let mut board = String::new();
         for y in 0..self.height {
             for x in 0..self.width {
-                let pos = Position(x, y);
-                match (self.game_over, self.won) {
-                    // won
-                    (true, true) => {
-                        if self.flags.contains(&pos) {
-                            if self.dinos.contains(&pos) {
-                                write!(board, "😼")?;
-                            } else {
-                                write!(board, "😨")?; //should never happen
-                            }
-                        } else if self.dinos.contains(&pos) {
-                            write!(board, "🦖")?;
-                        } else if self.seen.contains(&pos) {
-                            let count = self.get_neighboring_dino_count(&Position(x, y));
-                            match count {
-                                0 => {
-                                    write!(board, "🌠")?;
-                                }
-                                _ => {
-                                    write!(board, "{}", count)?;
-                                }
-                            }
-                        } else {
-                            write!(board, "🌺")?;
-                        }
-                    }
-                    // lost
-                    (true, false) => {
-                        if self.flags.contains(&Position(x, y)) {
-                            if self.game_over {
-                                if !self.dinos.contains(&Position(x, y)) {
-                                    //write!(board, "❌️")?;//
-                                    write!(board, "😵")?; //
-                                } else {
-                                    write!(board, "😬")?;
-                                }
-                            }
-                        } else if self.dinos.contains(&pos) {
-                            write!(board, "🦖")?;
-                        } else if self.seen.contains(&pos) {
-                            let count = self.get_neighboring_dino_count(&Position(x, y));
-                            match count {
-                                0 => {
-                                    write!(board, "🔲")?;
-                                }
-                                _ => {
-                                    write!(board, "{}", count)?;
-                                }
-                            }
-                        } else {
-                            write!(board, "🍂")?;
-                        }
-                    }
-                    // game is still running
-                    (false, _) => {
-                        if self.flags.contains(&pos) {
-                            write!(board, "🚩")?;
-                        } else if self.seen.contains(&Position(x, y)) {
-                            let count = self.get_neighboring_dino_count(&Position(x, y));
-                            match count {
-                                0 => {
-                                    write!(board, "🔲")?;
-                                }
-                                _ => {
-                                    write!(board, "{}", count)?;
-                                }
-                            }
-                        } else {
-                            write!(board, "🌿")?;
-                        }
-                    }
-                }
+                write!(board, "{}", self.symbol(&Position(x, y), Charset::Emoji))?;
             }
             writeln!(board)?;
         }
@@ -290,23 +653,30 @@ impl Display for Dinomite {
 
 #[cfg(test)]
 pub mod test {
+    use crate::components::lib::board::Board;
     use crate::components::lib::dinomite::PositionResult::DinosInSurrounding;
-    use crate::components::lib::dinomite::{Dinomite, Position, PositionResult};
-    use std::collections::HashSet;
+    use crate::components::lib::dinomite::{
+        Charset, Dinomite, FieldView, GameStatus, Position, PositionResult, column_label,
+        random_fields,
+    };
+    use crate::components::lib::rng::SplitMix64;
+    use crate::components::lib::topology::{Neighborhood, Rectangular, TopologyKind};
+    use proptest::prelude::*;
+    use std::collections::{HashMap, HashSet};
 
     #[test]
     fn test_repr() {
         let expected = 5;
         let dinomite = Dinomite::new(10, 10, expected);
         print!("{}", dinomite);
-        assert_eq!(dinomite.dinos.len(), expected);
+        assert_eq!(dinomite.get_num_dinos(), expected);
     }
     #[test]
     fn test_repr2() {
         let expected = 100;
         let dinomite = Dinomite::new(10, 10, expected);
         print!("{}", dinomite);
-        assert_eq!(dinomite.dinos.len(), expected);
+        assert_eq!(dinomite.get_num_dinos(), expected);
     }
     /* #[test]
     fn test_reset() {
@@ -315,17 +685,13 @@ pub mod test {
         println!("{}", dinomite);
         dinomite.reconfigure(20, 20, expected);
         println!("{}", dinomite);
-        assert_eq!(dinomite.dinos.len(), expected);
+        assert_eq!(dinomite.get_num_dinos(), expected);
     }*/
 
     #[test]
     fn test_neighbors() {
-        let expected: HashSet<Position> = HashSet::from([
-            Position(0, 0),
-            Position(0, 1),
-            Position(1, 0),
-            Position(1, 1),
-        ]);
+        let expected: HashSet<Position> =
+            HashSet::from([Position(0, 1), Position(1, 0), Position(1, 1)]);
         let dinomite = Dinomite::new(10, 10, 5);
         println!("{}", dinomite);
         println!("{}", dinomite);
@@ -337,13 +703,400 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn test_counts_match_brute_force() {
+        let mut dinomite = Dinomite::new(7, 5, 12);
+        dinomite.place_pending_dinos(&Position(0, 0));
+        for y in 0..5usize {
+            for x in 0..7usize {
+                let mut expected = 0;
+                for ny in y.saturating_sub(1)..=(y + 1).min(4) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(6) {
+                        if (nx, ny) != (x, y) && dinomite.cells[ny * 7 + nx].dinos > 0 {
+                            expected += 1;
+                        }
+                    }
+                }
+                assert_eq!(
+                    dinomite.get_neighboring_dino_count(&Position(x, y)),
+                    expected,
+                    "{x}, {y}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_safe_start() {
+        // exactly enough room for all dinos outside the 3x3 block around the first click
+        for _ in 0..20 {
+            let mut dinomite = Dinomite::new(5, 5, 16);
+            dinomite.set_safe_start(true);
+            assert_eq!(
+                dinomite.check_position(&Position(2, 2)),
+                PositionResult::Clear
+            );
+            assert_eq!(dinomite.get_num_dinos(), 16);
+        }
+    }
+
+    #[test]
+    fn test_safe_start_crowded() {
+        // no room for a free 3x3 block, but the first field itself stays safe
+        let mut dinomite = Dinomite::new(5, 5, 24);
+        dinomite.set_safe_start(true);
+        assert_eq!(
+            dinomite.check_position(&Position(2, 2)),
+            DinosInSurrounding(8)
+        );
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn test_seed() {
+        let layout = |seed: u64| {
+            let mut dinomite = Dinomite::new(16, 16, 40);
+            dinomite.set_seed(seed);
+            dinomite.place_pending_dinos(&Position(0, 0));
+            assert_eq!(dinomite.seed(), Some(seed));
+            dinomite.to_board().unwrap().dinos
+        };
+        assert_eq!(layout(20261018), layout(20261018));
+        assert_ne!(layout(20261018), layout(20261019));
+
+        let mut dinomite = Dinomite::new(9, 9, 10);
+        assert_eq!(dinomite.seed(), None);
+        dinomite.check_position(&Position(4, 4));
+        let seed = dinomite.seed().unwrap();
+        let mut replayed = Dinomite::new(9, 9, 10);
+        replayed.set_seed(seed);
+        replayed.check_position(&Position(4, 4));
+        assert_eq!(replayed.to_board(), dinomite.to_board());
+    }
+
+    #[test]
+    fn test_seeded_layout_is_pinned() {
+        let mut dinomite = Dinomite::new(9, 9, 10);
+        dinomite.set_seed(42);
+        dinomite.place_pending_dinos(&Position(4, 4));
+        let dinos: Vec<usize> = (0..81)
+            .filter(|&idx| dinomite.cells[idx].dinos > 0)
+            .collect();
+        // the same for everybody, so daily boards and shared seeds stay valid
+        assert_eq!(dinos, [17, 27, 31, 38, 45, 46, 47, 52, 58, 63]);
+    }
+
+    #[test]
+    fn test_full_board() {
+        let mut dinomite = Dinomite::new(30, 30, 30 * 30);
+        assert_eq!(
+            dinomite.check_position(&Position(3, 3)),
+            PositionResult::Dino
+        );
+        assert_eq!(dinomite.get_num_dinos(), 30 * 30);
+    }
+
+    #[test]
+    fn test_random_fields_per_field_frequency() {
+        let mut rng = SplitMix64::new(42);
+        let (num_fields, amount, trials) = (25, 5, 20_000);
+        let excluded = [0, 1, 5, 6];
+        let mut hits = [0usize; 25];
+        for _ in 0..trials {
+            let fields = random_fields(&mut rng, num_fields, amount, &excluded);
+            assert_eq!(fields.len(), amount);
+            assert_eq!(fields.iter().collect::<HashSet<_>>().len(), amount);
+            for idx in fields {
+                hits[idx] += 1;
+            }
+        }
+        // each allowed field is picked with probability amount / allowed
+        let p = amount as f64 / (num_fields - excluded.len()) as f64;
+        let expected = trials as f64 * p;
+        let sigma = (trials as f64 * p * (1.0 - p)).sqrt();
+        for (idx, &count) in hits.iter().enumerate() {
+            if excluded.contains(&idx) {
+                assert_eq!(count, 0);
+            } else {
+                assert!(
+                    (count as f64 - expected).abs() < 5.0 * sigma,
+                    "field {idx}: {count} vs {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_fields_layout_frequency() {
+        // all 6 ways to hide 2 dinos in 4 fields should be equally likely
+        let mut rng = SplitMix64::new(7);
+        let trials = 60_000;
+        let mut layouts: HashMap<Vec<usize>, usize> = HashMap::new();
+        for _ in 0..trials {
+            let mut fields = random_fields(&mut rng, 4, 2, &[]);
+            fields.sort();
+            *layouts.entry(fields).or_default() += 1;
+        }
+        assert_eq!(layouts.len(), 6);
+        let expected = trials as f64 / 6.0;
+        let sigma = (trials as f64 / 6.0 * 5.0 / 6.0).sqrt();
+        for (layout, count) in layouts {
+            assert!(
+                (count as f64 - expected).abs() < 5.0 * sigma,
+                "{layout:?}: {count} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chord() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(4, 4));
+        assert_eq!(
+            dinomite.check_position(&Position(1, 1)),
+            DinosInSurrounding(1)
+        );
+        // not enough flags yet, and nothing to chord on covered fields
+        assert_eq!(dinomite.chord(&Position(1, 1)), PositionResult::Unchanged);
+        assert_eq!(dinomite.num_seen, 1);
+        dinomite.toggle_flag(&Position(1, 0));
+        assert_eq!(dinomite.chord(&Position(2, 2)), PositionResult::Unchanged);
+        assert_eq!(dinomite.chord(&Position(1, 0)), PositionResult::Unchanged);
+        dinomite.toggle_flag(&Position(1, 0));
+        assert_eq!(dinomite.get_num_flags(), 0);
+        assert_eq!(dinomite.num_seen, 1);
+
+        dinomite.toggle_flag(&Position(0, 0));
+        assert_eq!(dinomite.chord(&Position(1, 1)), DinosInSurrounding(1));
+        // (2, 2) has no dinos nearby, so the opening spreads over the whole board
+        assert_eq!(dinomite.num_seen, 5 * 5 - 2);
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn test_chord_wrong_flag() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(4, 4));
+        dinomite.check_position(&Position(1, 1));
+        dinomite.toggle_flag(&Position(1, 0));
+        assert_eq!(dinomite.chord(&Position(1, 1)), PositionResult::Dino);
+        assert_eq!(
+            dinomite.status,
+            GameStatus::Lost {
+                exploded_at: Position(0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_torus_counts() {
+        let mut dinomite =
+            Dinomite::with_topology(5, 5, 0, TopologyKind::Torus.topology(Neighborhood::King));
+        dinomite.set_dino(&Position(4, 4));
+        dinomite.set_dino(&Position(0, 4));
+        assert_eq!(
+            dinomite.check_position(&Position(0, 0)),
+            DinosInSurrounding(2)
+        );
+        assert_eq!(
+            dinomite.check_position(&Position(2, 2)),
+            PositionResult::Clear
+        );
+        // the opening spreads across the edges, but not onto the dinos' neighbors
+        assert_eq!(dinomite.num_seen, 5 * 5 - 2);
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn test_several_dinos_per_field() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dinos_per_field(3);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(2, 0));
+        assert_eq!(dinomite.get_num_dinos(), 3);
+        assert_eq!(
+            dinomite.check_position(&Position(1, 0)),
+            DinosInSurrounding(3)
+        );
+        assert_eq!(dinomite.symbol(&Position(1, 0), Charset::Emoji), "3");
+
+        // two flags on (0, 0) and one on (2, 0) satisfy the 3
+        dinomite.toggle_flag(&Position(0, 0));
+        dinomite.toggle_flag(&Position(0, 0));
+        assert_eq!(dinomite.symbol(&Position(0, 0), Charset::Emoji), "2🚩");
+        dinomite.toggle_flag(&Position(2, 0));
+        assert_eq!(dinomite.get_num_flags(), 3);
+        assert_eq!(dinomite.get_remaining_dinos(), 0);
+        dinomite.chord(&Position(1, 0));
+        assert!(dinomite.cells[dinomite.index(&Position(1, 1))].seen);
+
+        // the whole board apart from the two dino fields can be uncovered
+        dinomite.check_position(&Position(4, 4));
+        assert_eq!(dinomite.status, GameStatus::Won);
+        assert_eq!(dinomite.symbol(&Position(0, 0), Charset::Emoji), "😼");
+    }
+
+    #[test]
+    fn test_question_marks() {
+        let mut dinomite = Dinomite::new(5, 5, 3);
+        dinomite.set_question_marks(true);
+        let pos = Position(1, 1);
+        dinomite.toggle_flag(&pos);
+        assert_eq!(dinomite.view(&pos), FieldView::Flagged(1));
+        dinomite.toggle_flag(&pos);
+        assert_eq!(dinomite.view(&pos), FieldView::Marked);
+        assert_eq!(dinomite.get_num_flags(), 0);
+        assert_eq!(dinomite.symbol(&pos, Charset::Ascii), "?");
+        dinomite.toggle_flag(&pos);
+        assert_eq!(dinomite.view(&pos), FieldView::Covered);
+
+        dinomite.toggle_flag(&pos);
+        dinomite.remove_flag(&pos);
+        dinomite.toggle_flag(&pos);
+        dinomite.toggle_flag(&pos);
+        dinomite.remove_flag(&pos);
+        assert_eq!(dinomite.view(&pos), FieldView::Covered);
+    }
+
+    #[test]
+    fn test_position_names() {
+        assert_eq!(column_label(0), "A");
+        assert_eq!(column_label(25), "Z");
+        assert_eq!(column_label(26), "AA");
+        assert_eq!(column_label(701), "ZZ");
+        assert_eq!(column_label(702), "AAA");
+        assert_eq!(Position(2, 6).to_string(), "C7");
+        for x in [0, 25, 26, 27, 700, 999] {
+            let pos = Position(x, 41);
+            assert_eq!(pos.to_string().parse::<Position>().unwrap(), pos);
+        }
+        assert_eq!("c7".parse::<Position>().unwrap(), Position(2, 6));
+        assert_eq!(" 2 6 ".parse::<Position>().unwrap(), Position(2, 6));
+        assert_eq!("2,6".parse::<Position>().unwrap(), Position(2, 6));
+        for invalid in ["", "C", "7", "C0", "7C", "C-1", "2 x", "1 2 3", "Ä1"] {
+            assert!(invalid.parse::<Position>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_flags_cycle() {
+        let mut dinomite = Dinomite::new(5, 5, 10);
+        dinomite.set_dinos_per_field(3);
+        let pos = Position(1, 1);
+        for expected in [1, 2, 3, 0] {
+            dinomite.toggle_flag(&pos);
+            assert_eq!(dinomite.cells[dinomite.index(&pos)].flags, expected);
+            assert_eq!(dinomite.get_num_flags(), expected as usize);
+        }
+        dinomite.toggle_flag(&pos);
+        dinomite.toggle_flag(&pos);
+        dinomite.remove_flag(&pos);
+        assert_eq!(dinomite.cells[dinomite.index(&pos)].flags, 1);
+        dinomite.remove_flag(&pos);
+        dinomite.remove_flag(&pos);
+        assert_eq!(dinomite.get_num_flags(), 0);
+    }
+
+    #[test]
+    fn test_place_several_dinos_per_field() {
+        for num_dinos in [30, 75] {
+            let mut dinomite = Dinomite::new(5, 5, num_dinos);
+            dinomite.set_dinos_per_field(3);
+            dinomite.place_pending_dinos(&Position(0, 0));
+            assert_eq!(dinomite.get_num_dinos(), num_dinos);
+            let total: usize = dinomite.cells.iter().map(|c| c.dinos as usize).sum();
+            assert_eq!(total, num_dinos);
+            assert!(dinomite.cells.iter().all(|c| c.dinos <= 3));
+            for (idx, cell) in dinomite.cells.iter().enumerate() {
+                let expected: usize = dinomite
+                    .get_neighbors(&dinomite.position(idx))
+                    .map(|n| dinomite.cells[dinomite.index(&n)].dinos as usize)
+                    .sum();
+                assert_eq!(cell.count as usize, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_board_roundtrip() {
+        let board: Board = "*....\n..2..\n.....\n.....\n.....\n".parse().unwrap();
+        let mut dinomite = Dinomite::from_board(&board, Box::new(Rectangular::default()));
+        assert_eq!(dinomite.get_num_dinos(), 3);
+        assert_eq!(dinomite.get_dinos_per_field(), 2);
+        assert_eq!(dinomite.status, GameStatus::NotStarted);
+        // the layout is fixed, the first click does not hide any more dinos
+        assert_eq!(
+            dinomite.check_position(&Position(1, 1)),
+            DinosInSurrounding(3)
+        );
+        dinomite.check_position(&Position(4, 4));
+        dinomite.toggle_flag(&Position(0, 0));
+
+        let exported = dinomite.to_board().unwrap();
+        assert_eq!(exported.dinos, board.dinos);
+        let restored = Dinomite::from_board(&exported, Box::new(Rectangular::default()));
+        assert_eq!(restored.cells, dinomite.cells);
+        assert_eq!(restored.num_seen, dinomite.num_seen);
+        assert_eq!(restored.get_num_flags(), 1);
+        assert_eq!(restored.status, GameStatus::Playing);
+    }
+
+    #[test]
+    fn test_board_state() {
+        let lost: Board =
+            "*....\n.....\n.....\n.....\n.....\n\no####\n#####\n#####\n#####\n#####\n"
+                .parse()
+                .unwrap();
+        let dinomite = Dinomite::from_board(&lost, Box::new(Rectangular::default()));
+        assert_eq!(
+            dinomite.status,
+            GameStatus::Lost {
+                exploded_at: Position(0, 0)
+            }
+        );
+        let won: Board = "*....\n.....\n.....\n.....\n.....\n\n#oooo\nooooo\nooooo\nooooo\nooooo\n"
+            .parse()
+            .unwrap();
+        let dinomite = Dinomite::from_board(&won, Box::new(Rectangular::default()));
+        assert_eq!(dinomite.status, GameStatus::Won);
+        assert!(Dinomite::new(5, 5, 3).to_board().is_none());
+    }
+
+    #[test]
+    fn test_knight_counts() {
+        let topology = TopologyKind::Rectangular.topology(Neighborhood::Knight);
+        let mut dinomite = Dinomite::with_topology(5, 5, 0, topology);
+        dinomite.set_dino(&Position(1, 2));
+        dinomite.set_dino(&Position(2, 1));
+        dinomite.set_dino(&Position(1, 1));
+        // only the two dinos a knight's move away count
+        assert_eq!(
+            dinomite.check_position(&Position(0, 0)),
+            DinosInSurrounding(2)
+        );
+        // (4, 4) has no dino a knight's move away, so the opening jumps to (3, 2) and (2, 3),
+        // which both see (1, 1) and stop it there
+        assert_eq!(
+            dinomite.check_position(&Position(4, 4)),
+            PositionResult::Clear
+        );
+        assert!(dinomite.cells[dinomite.index(&Position(3, 2))].seen);
+        assert!(dinomite.cells[dinomite.index(&Position(2, 3))].seen);
+        assert!(!dinomite.cells[dinomite.index(&Position(3, 3))].seen);
+        assert_eq!(dinomite.num_seen, 4);
+        assert_eq!(dinomite.status, GameStatus::Playing);
+    }
+
     #[test]
     fn test_surrounding() {
         let expected: PositionResult = DinosInSurrounding(3);
         let mut dinomite = Dinomite::new(10, 10, 0);
-        dinomite.dinos.insert(Position(0, 0));
-        dinomite.dinos.insert(Position(1, 0));
-        dinomite.dinos.insert(Position(1, 1));
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(1, 0));
+        dinomite.set_dino(&Position(1, 1));
         let pos = Position(0, 1);
         println!("{}", dinomite);
         assert_eq!(dinomite.check_position(&pos), expected);
@@ -353,8 +1106,8 @@ pub mod test {
     fn test_toggle_flag() {
         let expected = 2;
         let mut dinomite = Dinomite::new(10, 10, 0);
-        dinomite.dinos.insert(Position(9, 9));
-        dinomite.dinos.insert(Position(8, 8));
+        dinomite.set_dino(&Position(9, 9));
+        dinomite.set_dino(&Position(8, 8));
 
         dinomite.toggle_flag(&Position(0, 0));
         dinomite.check_position(&Position(0, 0));
@@ -363,9 +1116,35 @@ pub mod test {
         dinomite.toggle_flag(&Position(1, 0));
         dinomite.toggle_flag(&Position(1, 1));
         println!("{}", dinomite);
-        println!("{:?}", dinomite.flags);
 
-        assert_eq!(dinomite.flags.len(), expected);
+        assert_eq!(dinomite.get_num_flags(), expected);
+    }
+
+    #[test]
+    fn test_remaining_dinos() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(4, 4));
+        dinomite.set_flag_limit(false);
+
+        dinomite.toggle_flag(&Position(0, 0));
+        assert_eq!(dinomite.get_remaining_dinos(), 0);
+        dinomite.toggle_flag(&Position(1, 0));
+        dinomite.toggle_flag(&Position(2, 0));
+
+        assert_eq!(dinomite.get_num_flags(), 3);
+        assert_eq!(dinomite.get_remaining_dinos(), -2);
+    }
+
+    #[test]
+    fn test_flag_limit() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(4, 4));
+
+        dinomite.toggle_flag(&Position(0, 0));
+        dinomite.toggle_flag(&Position(1, 0));
+
+        assert_eq!(dinomite.get_num_flags(), 1);
+        assert_eq!(dinomite.get_remaining_dinos(), 0);
     }
 
     #[test]
@@ -373,20 +1152,15 @@ pub mod test {
         let expected = 24;
         let mut dinomite = Dinomite::new(5, 5, 0);
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
 
-        dinomite.dinos.insert(Position(0, 0));
+        dinomite.set_dino(&Position(0, 0));
 
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
 
         dinomite.check_position(&Position(4, 4));
-        for s in &dinomite.seen {
-            println!("{:?}", s);
-        }
         println!("{}", dinomite);
 
-        assert_eq!(dinomite.seen.len(), expected);
+        assert_eq!(dinomite.num_seen, expected);
     }
 
     #[test]
@@ -394,14 +1168,13 @@ pub mod test {
         let pos = Position(1, 1);
         let expected = 2;
         let mut dinomite = Dinomite::new(5, 5, 0);
-        dinomite.dinos.insert(Position(0, 0));
-        dinomite.dinos.insert(Position(0, 1));
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.set_dino(&Position(0, 1));
 
         println!("{}", dinomite);
 
         dinomite.check_position(&pos);
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
 
         assert_eq!(dinomite.get_neighboring_dino_count(&pos), expected);
     }
@@ -409,21 +1182,19 @@ pub mod test {
     fn test_check_loose() {
         let pos = Position(1, 1);
         let mut dinomite = Dinomite::new(3, 3, 0);
-        dinomite.dinos.insert(pos.clone());
+        dinomite.set_dino(&pos);
         println!("{}", dinomite);
         dinomite.check_position(&pos);
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
 
-        assert!(!dinomite.won);
-        assert!(dinomite.game_over);
+        assert_eq!(dinomite.status, GameStatus::Lost { exploded_at: pos });
     }
     #[test]
     fn test_check_win() {
         let pos = Position(1, 1);
         let mut dinomite = Dinomite::new(3, 3, 0);
-        dinomite.dinos.insert(pos.clone());
-        dinomite.flags.insert(pos.clone());
+        dinomite.set_dino(&pos);
+        dinomite.toggle_flag(&pos);
         println!("{}", dinomite);
         dinomite.check_position(&Position(0, 0));
         dinomite.check_position(&Position(0, 1));
@@ -437,25 +1208,21 @@ pub mod test {
         dinomite.check_position(&Position(2, 2));
 
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
 
-        assert!(dinomite.won);
-        assert!(dinomite.game_over);
+        assert_eq!(dinomite.status, GameStatus::Won);
     }
     #[test]
     fn test_check_flag_click_protection() {
         let flag = Position(0, 0);
         let dino = Position(0, 0);
         let mut dinomite = Dinomite::new(5, 5, 0);
-        dinomite.dinos.insert(dino.clone());
-        dinomite.flags.insert(flag.clone());
+        dinomite.set_dino(&dino);
+        dinomite.toggle_flag(&flag);
         println!("{}", dinomite);
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
         dinomite.check_position(&flag); // this should not do anything
         println!("{}", dinomite);
-        println!("{:?}", dinomite.seen);
-        assert!(!dinomite.game_over);
+        assert_eq!(dinomite.status, GameStatus::NotStarted);
     }
 
     #[test]
@@ -464,4 +1231,106 @@ pub mod test {
         let d = Dinomite::new(10, 10, expected);
         assert_eq!(d.get_num_dinos(), expected)
     }
+
+    #[test]
+    fn test_win_by_cascade() {
+        // the last safe cells are uncovered by a flood fill
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.check_position(&Position(0, 1));
+        assert_eq!(dinomite.status, GameStatus::Playing);
+        dinomite.check_position(&Position(4, 4));
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn test_win_by_first_click() {
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.check_position(&Position(4, 4));
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn test_reveal_huge_board() {
+        // used to overflow the stack when revealing recursively
+        let mut dinomite = Dinomite::new(1000, 1000, 0);
+        dinomite.set_dino(&Position(0, 0));
+        dinomite.check_position(&Position(999, 999));
+        assert_eq!(dinomite.num_seen, 1000 * 1000 - 1);
+        assert_eq!(dinomite.status, GameStatus::Won);
+    }
+
+    fn board() -> impl Strategy<Value = (usize, usize, Vec<Position>)> {
+        (2usize..8, 2usize..8).prop_flat_map(|(width, height)| {
+            let cells = (0..width * height)
+                .map(|i| Position(i % width, i / width))
+                .collect::<Vec<_>>();
+            (
+                Just(width),
+                Just(height),
+                proptest::sample::subsequence(cells, 0..width * height),
+            )
+        })
+    }
+
+    fn with_dinos(width: usize, height: usize, dinos: &[Position]) -> Dinomite {
+        let mut dinomite = Dinomite::new(width, height, 0);
+        for pos in dinos {
+            dinomite.set_dino(pos);
+        }
+        dinomite
+    }
+
+    proptest! {
+        #[test]
+        fn prop_won_iff_all_safe_cells_seen(
+            (width, height, dinos) in board(),
+            moves in proptest::collection::vec((0usize..8, 0usize..8), 1..40),
+        ) {
+            let mut dinomite = with_dinos(width, height, &dinos);
+            let num_safe = width * height - dinos.len();
+            for (x, y) in moves {
+                let pos = Position(x % width, y % height);
+                let before = dinomite.status.clone();
+                dinomite.check_position(&pos);
+                if matches!(before, GameStatus::Won | GameStatus::Lost { .. }) {
+                    prop_assert_eq!(&dinomite.status, &before);
+                    continue;
+                }
+                if dinos.contains(&pos) {
+                    prop_assert_eq!(&dinomite.status, &GameStatus::Lost { exploded_at: pos });
+                } else {
+                    prop_assert!(dinomite.cells.iter().all(|c| !(c.seen && c.dinos > 0)));
+                    prop_assert_eq!(
+                        dinomite.status == GameStatus::Won,
+                        dinomite.num_seen == num_safe
+                    );
+                    prop_assert_ne!(&dinomite.status, &GameStatus::NotStarted);
+                }
+            }
+        }
+
+        #[test]
+        fn prop_uncovering_every_safe_cell_wins(
+            (width, height, dinos, order) in board().prop_flat_map(|(width, height, dinos)| {
+                let safe = (0..width * height)
+                    .map(|i| Position(i % width, i / width))
+                    .filter(|p| !dinos.contains(p))
+                    .collect::<Vec<_>>();
+                (Just(width), Just(height), Just(dinos), Just(safe).prop_shuffle())
+            }),
+        ) {
+            prop_assume!(!order.is_empty());
+            let mut dinomite = with_dinos(width, height, &dinos);
+            for pos in &order {
+                dinomite.check_position(pos);
+                if dinomite.status == GameStatus::Won {
+                    // a cascade may uncover the rest early
+                    prop_assert_eq!(dinomite.num_seen, order.len());
+                }
+            }
+            prop_assert_eq!(&dinomite.status, &GameStatus::Won);
+        }
+    }
 }
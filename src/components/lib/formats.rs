@@ -0,0 +1,370 @@
+use crate::cli::{MAX_HEIGHT, MAX_WIDTH, MIN_SIZE};
+use crate::components::lib::analysis::analyze;
+use crate::components::lib::board::Board;
+use crate::components::lib::dinomite::{Dinomite, GameStatus, Position};
+use crate::components::lib::replay::{Move, Replay};
+use crate::components::lib::topology::Rectangular;
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::eyre};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+use strum::Display;
+
+// Pixels per field in RAWVF mouse coordinates.
+const RAWVF_SQUARE_SIZE: usize = 16;
+
+/// File formats boards and games can be read from and written to.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BoardFormat {
+    /// Our own text format, see `Board`
+    #[default]
+    Text,
+    /// Minesweeper Board Format: the layout as binary dino coordinates
+    Mbf,
+    /// RAW Minesweeper video format: the layout plus a replay of the moves
+    Rawvf,
+}
+
+impl BoardFormat {
+    // Guess the format from the file extension, falling back to our own text format.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("mbf") => BoardFormat::Mbf,
+            Some(e) if e.eq_ignore_ascii_case("rawvf") => BoardFormat::Rawvf,
+            _ => BoardFormat::Text,
+        }
+    }
+    pub fn extension(self) -> &'static str {
+        match self {
+            BoardFormat::Text => "txt",
+            BoardFormat::Mbf => "mbf",
+            BoardFormat::Rawvf => "rawvf",
+        }
+    }
+    // Read a board; RAWVF replays are played on it so the game continues where they ended.
+    // RAWVF comes from rectangular boards, so the replay is always played on one, whatever
+    // topology the game is played with afterwards.
+    pub fn read(self, bytes: &[u8]) -> Result<Board> {
+        match self {
+            BoardFormat::Text => std::str::from_utf8(bytes)?.parse(),
+            BoardFormat::Mbf => read_mbf(bytes),
+            BoardFormat::Rawvf => {
+                let (board, replay) = read_rawvf(std::str::from_utf8(bytes)?)?;
+                let mut dinomite = Dinomite::from_board(&board, Box::new(Rectangular::default()));
+                replay.apply(&mut dinomite);
+                Ok(dinomite.to_board().unwrap_or(board))
+            }
+        }
+    }
+    // Write the layout of board, along with the replay if the format has room for it.
+    pub fn write(self, board: &Board, replay: &Replay) -> Result<Vec<u8>> {
+        if self != BoardFormat::Text && board.dinos_per_field() > 1 {
+            return Err(eyre!("{self} cannot hold several dinos in one field"));
+        }
+        match self {
+            BoardFormat::Text => Ok(board.to_string().into_bytes()),
+            BoardFormat::Mbf => write_mbf(board),
+            BoardFormat::Rawvf => Ok(write_rawvf(board, replay).into_bytes()),
+        }
+    }
+}
+
+// MBF: width and height as one byte each, the number of dinos as two bytes big endian,
+// then x and y of every dino as one byte each.
+fn read_mbf(bytes: &[u8]) -> Result<Board> {
+    let [width, height, hi, lo, coordinates @ ..] = bytes else {
+        return Err(eyre!("MBF header too short"));
+    };
+    let (width, height) = (*width as usize, *height as usize);
+    let num_dinos = u16::from_be_bytes([*hi, *lo]) as usize;
+    if coordinates.len() != 2 * num_dinos {
+        return Err(eyre!(
+            "MBF announces {num_dinos} dinos, but has coordinates for {}",
+            coordinates.len() / 2
+        ));
+    }
+    let mut dinos = vec![0; width * height];
+    for xy in coordinates.chunks(2) {
+        let (x, y) = (xy[0] as usize, xy[1] as usize);
+        if x >= width || y >= height {
+            return Err(eyre!(
+                "MBF dino at {x}, {y} is outside the {width}x{height} board"
+            ));
+        }
+        if dinos[y * width + x] > 0 {
+            return Err(eyre!("MBF has two dinos at {x}, {y}"));
+        }
+        dinos[y * width + x] = 1;
+    }
+    Ok(Board {
+        width,
+        height,
+        dinos,
+        state: None,
+    })
+}
+
+fn write_mbf(board: &Board) -> Result<Vec<u8>> {
+    let (Ok(width), Ok(height), Ok(num_dinos)) = (
+        u8::try_from(board.width),
+        u8::try_from(board.height),
+        u16::try_from(board.num_dinos()),
+    ) else {
+        return Err(eyre!("MBF only fits boards up to 255x255 with 65535 dinos"));
+    };
+    let mut bytes = vec![width, height];
+    bytes.extend(num_dinos.to_be_bytes());
+    for (idx, _) in board.dinos.iter().enumerate().filter(|(_, d)| **d > 0) {
+        bytes.extend([(idx % board.width) as u8, (idx / board.width) as u8]);
+    }
+    Ok(bytes)
+}
+
+// RAWVF: "Key: value" header lines, the layout after "Board:" with `*` for a dino and
+// `0` for a safe field, then one timed event per line after "Events:". Only the mouse
+// button releases on a field are turned into moves.
+pub fn read_rawvf(text: &str) -> Result<(Board, Replay)> {
+    let mut lines = text.lines().map(str::trim).enumerate();
+    let mut header = std::collections::HashMap::new();
+    for (_, line) in lines.by_ref() {
+        if line == "Board:" {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            header.insert(key.trim(), value.trim());
+        }
+    }
+    let number = |key: &str| -> Result<usize> {
+        header
+            .get(key)
+            .ok_or_else(|| eyre!("RAWVF header lacks {key}"))?
+            .parse()
+            .map_err(|e| eyre!("RAWVF header {key}: {e}"))
+    };
+    let (width, height, num_dinos) = (number("Width")?, number("Height")?, number("Mines")?);
+    // checked before anything is allocated for the fields
+    if !(MIN_SIZE..=MAX_WIDTH).contains(&width) || !(MIN_SIZE..=MAX_HEIGHT).contains(&height) {
+        return Err(eyre!(
+            "RAWVF board is {width}x{height}, but has to be between {MIN_SIZE}x{MIN_SIZE} and {MAX_WIDTH}x{MAX_HEIGHT}"
+        ));
+    }
+
+    let mut dinos = Vec::with_capacity(width * height);
+    for (number, line) in lines.by_ref().take(height) {
+        let row = line
+            .chars()
+            .map(|c| match c {
+                '*' => Ok(1),
+                '0' | '.' => Ok(0),
+                _ => Err(eyre!("line {}: unexpected '{c}' in the board", number + 1)),
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        if row.len() != width {
+            return Err(eyre!("line {}: expected {width} fields", number + 1));
+        }
+        dinos.extend(row);
+    }
+    let board = Board {
+        width,
+        height,
+        dinos,
+        state: None,
+    };
+    if board.dinos.len() != width * height {
+        return Err(eyre!("RAWVF board has fewer than {height} rows"));
+    }
+    if board.num_dinos() != num_dinos {
+        return Err(eyre!(
+            "RAWVF header announces {num_dinos} mines, the board has {}",
+            board.num_dinos()
+        ));
+    }
+
+    let mut replay = Replay::default();
+    for (number, line) in lines.skip_while(|(_, l)| *l != "Events:").skip(1) {
+        let tokens: Vec<&str> = line
+            .split_whitespace()
+            .map(|t| t.trim_matches(|c| c == '(' || c == ')'))
+            .filter(|t| !t.is_empty())
+            .collect();
+        let action = match tokens.get(1) {
+            Some(&"lr") => Move::Open,
+            Some(&"rr") => Move::Flag,
+            Some(&"mr") => Move::Chord,
+            _ => continue,
+        };
+        let parse = |i: usize| -> Result<f64> {
+            tokens
+                .get(i)
+                .ok_or_else(|| eyre!("line {}: incomplete event", number + 1))?
+                .parse()
+                .map_err(|e| eyre!("line {}: {e}", number + 1))
+        };
+        let millis = (parse(0)?.max(0.0) * 1000.0).round() as u64;
+        // prefer the field in parentheses over the pixel coordinates
+        let (x, y) = match (parse(4), parse(5)) {
+            (Ok(col), Ok(row)) => (col as isize - 1, row as isize - 1),
+            _ => (
+                (parse(2)? as usize / RAWVF_SQUARE_SIZE) as isize,
+                (parse(3)? as usize / RAWVF_SQUARE_SIZE) as isize,
+            ),
+        };
+        if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+            continue;
+        }
+        replay.record(millis, action, Position(x as usize, y as usize));
+    }
+    Ok((board, replay))
+}
+
+fn write_rawvf(board: &Board, replay: &Replay) -> String {
+    let level = match (board.width, board.height, board.num_dinos()) {
+        (8, 8, 10) | (9, 9, 10) => "Beginner",
+        (16, 16, 40) => "Intermediate",
+        (30, 16, 99) => "Expert",
+        _ => "Custom",
+    };
+    let seconds = |millis: u64| millis as f64 / 1000.0;
+    let mut out = String::new();
+    let _ = writeln!(out, "RawVF_Version: Rev5");
+    let _ = writeln!(out, "Program: {}", env!("CARGO_PKG_NAME"));
+    let _ = writeln!(out, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "Level: {level}");
+    let _ = writeln!(out, "Width: {}", board.width);
+    let _ = writeln!(out, "Height: {}", board.height);
+    let _ = writeln!(out, "Mines: {}", board.num_dinos());
+    let _ = writeln!(out, "Marks: Off");
+    let _ = writeln!(out, "SquareSize: {RAWVF_SQUARE_SIZE}");
+    let _ = writeln!(out, "Time: {:.3}", seconds(replay.duration_millis()));
+    let _ = writeln!(
+        out,
+        "BBBV: {}",
+        analyze(board, &Rectangular::default()).bbbv
+    );
+    let _ = writeln!(out, "Board:");
+    for row in board.dinos.chunks(board.width) {
+        let row: String = row.iter().map(|&d| if d > 0 { '*' } else { '0' }).collect();
+        let _ = writeln!(out, "{row}");
+    }
+    let _ = writeln!(out, "Events:");
+    let _ = writeln!(out, "0.000 start");
+    for event in &replay.events {
+        let buttons = match event.action {
+            Move::Open => ["lc", "lr"],
+            Move::Flag | Move::Unflag => ["rc", "rr"],
+            Move::Chord => ["mc", "mr"],
+        };
+        let Position(x, y) = event.pos;
+        for button in buttons {
+            let _ = writeln!(
+                out,
+                "{:.3} {button} {} {} ({} {})",
+                seconds(event.millis),
+                x * RAWVF_SQUARE_SIZE + RAWVF_SQUARE_SIZE / 2,
+                y * RAWVF_SQUARE_SIZE + RAWVF_SQUARE_SIZE / 2,
+                x + 1,
+                y + 1
+            );
+        }
+    }
+    let mut dinomite = Dinomite::from_board(
+        &Board {
+            state: None,
+            ..board.clone()
+        },
+        Box::new(Rectangular::default()),
+    );
+    replay.apply(&mut dinomite);
+    match dinomite.status() {
+        GameStatus::Won => {
+            let _ = writeln!(out, "{:.3} won", seconds(replay.duration_millis()));
+        }
+        GameStatus::Lost { .. } => {
+            let _ = writeln!(out, "{:.3} blast", seconds(replay.duration_millis()));
+        }
+        GameStatus::NotStarted | GameStatus::Playing => {}
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn board() -> Board {
+        "*....\n.....\n..*..\n.....\n....*\n".parse().unwrap()
+    }
+
+    #[test]
+    fn test_mbf_roundtrip() {
+        let bytes = BoardFormat::Mbf
+            .write(&board(), &Replay::default())
+            .unwrap();
+        assert_eq!(bytes, [5, 5, 0, 3, 0, 0, 2, 2, 4, 4]);
+        assert_eq!(BoardFormat::Mbf.read(&bytes).unwrap(), board());
+    }
+
+    #[test]
+    fn test_mbf_errors() {
+        assert!(read_mbf(&[5, 5, 0]).is_err());
+        assert!(read_mbf(&[5, 5, 0, 2, 0, 0]).is_err());
+        assert!(read_mbf(&[5, 5, 0, 1, 5, 0]).is_err());
+        assert!(read_mbf(&[5, 5, 0, 2, 1, 1, 1, 1]).is_err());
+        let mut big = board();
+        big.width = 300;
+        assert!(write_mbf(&big).is_err());
+    }
+
+    #[test]
+    fn test_rawvf_roundtrip() {
+        let mut replay = Replay::default();
+        replay.record(250, Move::Flag, Position(0, 0));
+        replay.record(1500, Move::Open, Position(1, 0));
+        replay.record(2750, Move::Chord, Position(1, 0));
+        let text = String::from_utf8(BoardFormat::Rawvf.write(&board(), &replay).unwrap()).unwrap();
+        assert!(text.contains("Width: 5\nHeight: 5\nMines: 3\n"));
+        assert!(text.contains("BBBV: 2\n"));
+        assert!(text.contains("Board:\n*0000\n00000\n00*00\n00000\n0000*\nEvents:\n"));
+        assert!(text.contains("1.500 lr 24 8 (2 1)\n"));
+        assert!(text.ends_with("2.750 mr 24 8 (2 1)\n"));
+
+        let (read_board, read_replay) = read_rawvf(&text).unwrap();
+        assert_eq!(read_board, board());
+        assert_eq!(read_replay, replay);
+    }
+
+    #[test]
+    fn test_rawvf_state() {
+        let text = "RawVF_Version: Rev5\nWidth: 5\nHeight: 5\nMines: 1\nBoard:\n*0000\n00000\n\
+                    00000\n00000\n00000\nEvents:\n0.000 start\n0.1 lc 72 72 (5 5)\n\
+                    0.1 lr 72 72 (5 5)\n0.100 won\n";
+        let board = BoardFormat::Rawvf.read(text.as_bytes()).unwrap();
+        let dinomite = Dinomite::from_board(&board, Box::new(Rectangular::default()));
+        assert_eq!(dinomite.status(), &GameStatus::Won);
+        // pixel coordinates are used if the field is missing
+        let (_, replay) = read_rawvf(&text.replace(" (5 5)", "")).unwrap();
+        assert_eq!(replay.events[0].pos, Position(4, 4));
+        assert!(read_rawvf(&text.replace("Mines: 1", "Mines: 2")).is_err());
+    }
+
+    #[test]
+    fn test_rawvf_size_limits() {
+        let text = "Width: 5\nHeight: 5\nMines: 1\nBoard:\n*0000\n";
+        for (width, height) in [
+            ("18446744073709551615", "5"),
+            ("100000", "100000"),
+            ("5", "4"),
+        ] {
+            let header = text
+                .replace("Width: 5", &format!("Width: {width}"))
+                .replace("Height: 5", &format!("Height: {height}"));
+            let e = read_rawvf(&header).unwrap_err();
+            assert!(e.to_string().contains("has to be between"), "{e}");
+        }
+    }
+}
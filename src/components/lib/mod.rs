@@ -1 +1,8 @@
+pub mod analysis;
+pub mod board;
+pub mod daily;
 pub mod dinomite;
+pub mod formats;
+pub mod replay;
+pub mod rng;
+pub mod topology;
@@ -0,0 +1,79 @@
+use crate::components::lib::dinomite::{Dinomite, Position};
+
+/// What the player did on a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Open,
+    Flag,
+    Unflag,
+    Chord,
+}
+
+/// A single move, timed from the start of the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub millis: u64,
+    pub action: Move,
+    pub pos: Position,
+}
+
+/// The moves of a game in the order they were made.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub events: Vec<Event>,
+}
+
+impl Replay {
+    pub fn record(&mut self, millis: u64, action: Move, pos: Position) {
+        self.events.push(Event {
+            millis,
+            action,
+            pos,
+        });
+    }
+    // Play all moves on dinomite, stopping early once the game is over.
+    pub fn apply(&self, dinomite: &mut Dinomite) {
+        for event in &self.events {
+            if dinomite.is_game_over() {
+                break;
+            }
+            match event.action {
+                Move::Open => {
+                    dinomite.check_position(&event.pos);
+                }
+                Move::Flag => dinomite.toggle_flag(&event.pos),
+                Move::Unflag => dinomite.remove_flag(&event.pos),
+                Move::Chord => {
+                    dinomite.chord(&event.pos);
+                }
+            }
+        }
+    }
+    // Time of the last move.
+    pub fn duration_millis(&self) -> u64 {
+        self.events.last().map_or(0, |e| e.millis)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::lib::dinomite::GameStatus;
+
+    #[test]
+    fn test_apply() {
+        let mut replay = Replay::default();
+        replay.record(0, Move::Flag, Position(0, 0));
+        replay.record(120, Move::Open, Position(4, 4));
+        // the game is won by now, so these are ignored
+        replay.record(300, Move::Open, Position(0, 0));
+        replay.record(400, Move::Open, Position(1, 1));
+
+        let mut dinomite = Dinomite::new(5, 5, 0);
+        dinomite.set_dino(&Position(0, 0));
+        replay.apply(&mut dinomite);
+        assert_eq!(dinomite.status(), &GameStatus::Won);
+        assert_eq!(dinomite.get_num_flags(), 1);
+        assert_eq!(replay.duration_millis(), 400);
+    }
+}
@@ -0,0 +1,487 @@
+use crate::components::lib::dinomite::Position;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+// The eight fields surrounding a field on a square grid.
+const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+// Only the fields sharing an edge.
+const CROSS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+// The fields a chess knight can jump to.
+const KNIGHT: [(isize, isize); 8] = [
+    (-1, -2),
+    (1, -2),
+    (-2, -1),
+    (2, -1),
+    (-2, 1),
+    (2, 1),
+    (-1, 2),
+    (1, 2),
+];
+// The 5x5 block around a field.
+const RADIUS_2: [(isize, isize); 24] = [
+    (-2, -2),
+    (-1, -2),
+    (0, -2),
+    (1, -2),
+    (2, -2),
+    (-2, -1),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (2, -1),
+    (-2, 0),
+    (-1, 0),
+    (1, 0),
+    (2, 0),
+    (-2, 1),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (2, 1),
+    (-2, 2),
+    (-1, 2),
+    (0, 2),
+    (1, 2),
+    (2, 2),
+];
+
+// Neighbors on a hexagonal grid stored in "odd-r" layout, where odd rows are shifted
+// half a field to the right. Which fields touch depends on the parity of the row.
+const HEX_EVEN_ROW: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+const HEX_ODD_ROW: [(isize, isize); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+/// Directions the cursor can be moved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+/// Describes how the fields of a board are connected.
+///
+/// Neighbors are found by applying each offset to a position and mapping the result back onto
+/// the board with `wrap`, which drops coordinates that have no corresponding field.
+pub trait Topology {
+    /// Offsets from pos to its neighbors. Must not contain `(0, 0)` or duplicates.
+    fn offsets(&self, pos: &Position) -> &[(isize, isize)];
+    /// Map the coordinates (x, y) onto a width x height board, if they lie on it.
+    fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<Position>;
+    /// Whether leaving the board on one side enters it again on the opposite side.
+    fn wraps(&self) -> bool {
+        false
+    }
+    /// Whether row is drawn shifted half a field to the right.
+    fn shifted(&self, row: usize) -> bool {
+        let _ = row; // to appease clippy
+        false
+    }
+    /// Offset to the next field from pos when moving in direction.
+    fn step(&self, pos: &Position, direction: Direction) -> (isize, isize) {
+        let _ = pos; // to appease clippy
+        match direction {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+/// The topologies selectable for a game.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TopologyKind {
+    #[default]
+    Rectangular,
+    Torus,
+    Hex,
+}
+
+impl TopologyKind {
+    // Square boards count dinos according to neighborhood, hex boards always use their six
+    // surrounding fields.
+    pub fn topology(self, neighborhood: Neighborhood) -> Box<dyn Topology> {
+        match self {
+            TopologyKind::Rectangular => Box::new(Rectangular { neighborhood }),
+            TopologyKind::Torus => Box::new(Torus { neighborhood }),
+            TopologyKind::Hex => Box::new(Hex),
+        }
+    }
+}
+
+/// Which fields count as neighbors on square boards.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Neighborhood {
+    /// The eight surrounding fields, like a chess king moves
+    #[default]
+    King,
+    /// The four fields sharing an edge
+    Cross,
+    /// The eight fields a knight's move away
+    Knight,
+    /// The 24 fields at most two steps away in each direction
+    Radius2,
+}
+
+impl Neighborhood {
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::King => &MOORE,
+            Neighborhood::Cross => &CROSS,
+            Neighborhood::Knight => &KNIGHT,
+            Neighborhood::Radius2 => &RADIUS_2,
+        }
+    }
+}
+
+// Coordinates on a width x height board without wrapping.
+fn bounded(x: isize, y: isize, width: usize, height: usize) -> Option<Position> {
+    if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+        return None;
+    }
+    Some(Position(x as usize, y as usize))
+}
+
+/// A plain rectangular board, fields on the edges simply have fewer neighbors.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rectangular {
+    pub neighborhood: Neighborhood,
+}
+
+impl Topology for Rectangular {
+    fn offsets(&self, _pos: &Position) -> &[(isize, isize)] {
+        self.neighborhood.offsets()
+    }
+    fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<Position> {
+        bounded(x, y, width, height)
+    }
+}
+
+/// A board whose opposite edges are glued together, so no field lies on an edge.
+///
+/// Boards need to be at least as large as the neighborhood (3x3, or 5x5 for knight's moves and
+/// radius 2), otherwise some neighbors coincide.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Torus {
+    pub neighborhood: Neighborhood,
+}
+
+impl Topology for Torus {
+    fn offsets(&self, _pos: &Position) -> &[(isize, isize)] {
+        self.neighborhood.offsets()
+    }
+    fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<Position> {
+        Some(Position(
+            x.rem_euclid(width as isize) as usize,
+            y.rem_euclid(height as isize) as usize,
+        ))
+    }
+    fn wraps(&self) -> bool {
+        true
+    }
+}
+
+/// A board of hexagons, every field away from the edges has six neighbors.
+///
+/// Odd rows are shifted half a field to the right, so moving straight up or down zigzags.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Hex;
+
+impl Topology for Hex {
+    fn offsets(&self, pos: &Position) -> &[(isize, isize)] {
+        if self.shifted(pos.1) {
+            &HEX_ODD_ROW
+        } else {
+            &HEX_EVEN_ROW
+        }
+    }
+    fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<Position> {
+        bounded(x, y, width, height)
+    }
+    fn shifted(&self, row: usize) -> bool {
+        row % 2 == 1
+    }
+    fn step(&self, pos: &Position, direction: Direction) -> (isize, isize) {
+        // diagonal moves go to the hexagons touching the upper and lower edges
+        let shift = self.shifted(pos.1) as isize;
+        match direction {
+            Direction::UpLeft => (shift - 1, -1),
+            Direction::UpRight => (shift, -1),
+            Direction::DownLeft => (shift - 1, 1),
+            Direction::DownRight => (shift, 1),
+            _ => Rectangular::default().step(pos, direction),
+        }
+    }
+}
+
+// All neighbors of pos according to topology.
+pub fn neighbors<'a>(
+    topology: &'a dyn Topology,
+    pos: &Position,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = Position> + 'a {
+    let (x, y) = (pos.0 as isize, pos.1 as isize);
+    topology
+        .offsets(pos)
+        .iter()
+        .filter_map(move |(dx, dy)| topology.wrap(x + dx, y + dy, width, height))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    // every field at most one step away in each direction, except pos itself
+    fn brute_force(pos: &Position, width: usize, height: usize) -> HashSet<Position> {
+        let mut result = HashSet::new();
+        for y in 0..height {
+            for x in 0..width {
+                if pos.0.abs_diff(x) <= 1 && pos.1.abs_diff(y) <= 1 && (x, y) != (pos.0, pos.1) {
+                    result.insert(Position(x, y));
+                }
+            }
+        }
+        result
+    }
+
+    // like brute_force, but distances are measured around the edges
+    fn torus_brute_force(pos: &Position, width: usize, height: usize) -> HashSet<Position> {
+        let distance = |a: usize, b: usize, size: usize| a.abs_diff(b).min(size - a.abs_diff(b));
+        let mut result = HashSet::new();
+        for y in 0..height {
+            for x in 0..width {
+                if distance(pos.0, x, width) <= 1
+                    && distance(pos.1, y, height) <= 1
+                    && (x, y) != (pos.0, pos.1)
+                {
+                    result.insert(Position(x, y));
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_rectangular_matches_brute_force() {
+        for (width, height) in [(1, 1), (1, 5), (5, 1), (2, 2), (3, 3), (5, 7), (9, 4)] {
+            for y in 0..height {
+                for x in 0..width {
+                    let pos = Position(x, y);
+                    let found: Vec<Position> =
+                        neighbors(&Rectangular::default(), &pos, width, height).collect();
+                    let unique: HashSet<Position> = found.iter().cloned().collect();
+                    assert_eq!(found.len(), unique.len(), "duplicates for {pos:?}");
+                    assert!(!unique.contains(&pos), "{pos:?} is its own neighbor");
+                    assert_eq!(unique, brute_force(&pos, width, height), "{pos:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_torus_matches_brute_force() {
+        for (width, height) in [(3, 3), (5, 5), (5, 7), (9, 4)] {
+            for y in 0..height {
+                for x in 0..width {
+                    let pos = Position(x, y);
+                    let found: Vec<Position> =
+                        neighbors(&Torus::default(), &pos, width, height).collect();
+                    let unique: HashSet<Position> = found.iter().cloned().collect();
+                    assert_eq!(found.len(), 8, "{pos:?}");
+                    assert_eq!(unique.len(), 8, "duplicates for {pos:?}");
+                    assert!(!unique.contains(&pos), "{pos:?} is its own neighbor");
+                    assert_eq!(unique, torus_brute_force(&pos, width, height), "{pos:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_torus_corner() {
+        let found: HashSet<Position> =
+            neighbors(&Torus::default(), &Position(0, 0), 5, 4).collect();
+        let expected = HashSet::from([
+            Position(4, 3),
+            Position(0, 3),
+            Position(1, 3),
+            Position(4, 0),
+            Position(1, 0),
+            Position(4, 1),
+            Position(0, 1),
+            Position(1, 1),
+        ]);
+        assert_eq!(found, expected);
+    }
+
+    // hexagons sharing an edge, using cube coordinates
+    fn hex_brute_force(pos: &Position, width: usize, height: usize) -> HashSet<Position> {
+        let cube = |x: usize, y: usize| {
+            let (x, y) = (x as isize, y as isize);
+            (x - (y - (y & 1)) / 2, y)
+        };
+        let (q, r) = cube(pos.0, pos.1);
+        let mut result = HashSet::new();
+        for y in 0..height {
+            for x in 0..width {
+                let (q2, r2) = cube(x, y);
+                let (dq, dr) = (q2 - q, r2 - r);
+                if (dq.abs() + dr.abs() + (dq + dr).abs()) / 2 == 1 {
+                    result.insert(Position(x, y));
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_hex_matches_brute_force() {
+        for (width, height) in [(1, 1), (1, 5), (5, 1), (2, 2), (3, 3), (5, 7), (9, 4)] {
+            for y in 0..height {
+                for x in 0..width {
+                    let pos = Position(x, y);
+                    let found: Vec<Position> = neighbors(&Hex, &pos, width, height).collect();
+                    let unique: HashSet<Position> = found.iter().cloned().collect();
+                    assert_eq!(found.len(), unique.len(), "duplicates for {pos:?}");
+                    assert_eq!(unique, hex_brute_force(&pos, width, height), "{pos:?}");
+                }
+            }
+        }
+        assert_eq!(neighbors(&Hex, &Position(2, 2), 5, 5).count(), 6);
+        assert_eq!(neighbors(&Hex, &Position(2, 3), 5, 5).count(), 6);
+    }
+
+    #[test]
+    fn test_hex_steps_reach_neighbors() {
+        let diagonals = [
+            Direction::UpLeft,
+            Direction::UpRight,
+            Direction::DownLeft,
+            Direction::DownRight,
+            Direction::Left,
+            Direction::Right,
+        ];
+        for pos in [Position(2, 2), Position(2, 3)] {
+            let reached: HashSet<Position> = diagonals
+                .iter()
+                .filter_map(|d| {
+                    let (dx, dy) = Hex.step(&pos, *d);
+                    Hex.wrap(pos.0 as isize + dx, pos.1 as isize + dy, 5, 5)
+                })
+                .collect();
+            assert_eq!(reached, neighbors(&Hex, &pos, 5, 5).collect(), "{pos:?}");
+        }
+    }
+
+    // fields (dx, dy) away that belong to the neighborhood
+    fn brute_force_mask(neighborhood: Neighborhood, dx: isize, dy: isize) -> bool {
+        match neighborhood {
+            Neighborhood::King => dx.abs().max(dy.abs()) == 1,
+            Neighborhood::Cross => dx.abs() + dy.abs() == 1,
+            Neighborhood::Knight => dx.abs() * dy.abs() == 2,
+            Neighborhood::Radius2 => (dx, dy) != (0, 0) && dx.abs().max(dy.abs()) <= 2,
+        }
+    }
+
+    #[test]
+    fn test_neighborhoods_match_brute_force() {
+        let neighborhoods = [
+            Neighborhood::King,
+            Neighborhood::Cross,
+            Neighborhood::Knight,
+            Neighborhood::Radius2,
+        ];
+        for neighborhood in neighborhoods {
+            let rectangular = Rectangular { neighborhood };
+            let torus = Torus { neighborhood };
+            for (width, height) in [(5, 5), (6, 8), (9, 5)] {
+                for y in 0..height {
+                    for x in 0..width {
+                        let pos = Position(x, y);
+                        let mut expected = HashSet::new();
+                        let mut expected_torus = HashSet::new();
+                        for ny in 0..height {
+                            for nx in 0..width {
+                                let (dx, dy) = (nx as isize - x as isize, ny as isize - y as isize);
+                                if brute_force_mask(neighborhood, dx, dy) {
+                                    expected.insert(Position(nx, ny));
+                                }
+                                // the shortest way around in either direction
+                                let around = |d: isize, size: usize| {
+                                    let d = d.rem_euclid(size as isize);
+                                    if d > size as isize / 2 {
+                                        d - size as isize
+                                    } else {
+                                        d
+                                    }
+                                };
+                                if brute_force_mask(
+                                    neighborhood,
+                                    around(dx, width),
+                                    around(dy, height),
+                                ) {
+                                    expected_torus.insert(Position(nx, ny));
+                                }
+                            }
+                        }
+                        let found: Vec<Position> =
+                            neighbors(&rectangular, &pos, width, height).collect();
+                        assert_eq!(found.len(), expected.len(), "{neighborhood} {pos:?}");
+                        assert_eq!(found.into_iter().collect::<HashSet<_>>(), expected);
+
+                        let found: Vec<Position> = neighbors(&torus, &pos, width, height).collect();
+                        assert_eq!(found.len(), neighborhood.offsets().len());
+                        assert_eq!(found.into_iter().collect::<HashSet<_>>(), expected_torus);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rectangular_counts() {
+        let (width, height) = (6, 4);
+        let count =
+            |x, y| neighbors(&Rectangular::default(), &Position(x, y), width, height).count();
+        // corners
+        assert_eq!(count(0, 0), 3);
+        assert_eq!(count(5, 0), 3);
+        assert_eq!(count(0, 3), 3);
+        assert_eq!(count(5, 3), 3);
+        // edges
+        assert_eq!(count(2, 0), 5);
+        assert_eq!(count(0, 2), 5);
+        assert_eq!(count(5, 1), 5);
+        assert_eq!(count(3, 3), 5);
+        // inside
+        assert_eq!(count(2, 2), 8);
+    }
+}
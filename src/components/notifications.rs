@@ -0,0 +1,289 @@
+use super::Component;
+use crate::action::Action;
+use color_eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+// How long a notification stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+// Notifications shown at once, older ones are only in the log.
+const MAX_TOASTS: usize = 3;
+// Widest a notification is drawn, longer messages are wrapped.
+const TOAST_WIDTH: u16 = 50;
+// Notifications kept for the log, the oldest are dropped first.
+const MAX_LOG: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn style(self) -> Style {
+        match self {
+            Severity::Warning => Style::default().fg(Color::Black).bg(Color::Yellow),
+            Severity::Error => Style::default().fg(Color::White).bg(Color::Red),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Notice {
+    severity: Severity,
+    message: String,
+    // how often it was sent in a row, e.g. by every failed frame
+    repeats: usize,
+    received: Instant,
+    // shown until then, unless the log has been opened since
+    toast_until: Option<Instant>,
+}
+
+impl Notice {
+    fn text(&self) -> String {
+        match self.repeats {
+            1 => self.message.clone(),
+            n => format!("{} ({n}x)", self.message),
+        }
+    }
+}
+
+/// Errors and warnings, shown briefly in the top right corner and kept in a log that can be
+/// opened and scrolled.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    // newest first
+    notices: VecDeque<Notice>,
+    log_visible: bool,
+    // lines scrolled down in the log
+    scroll: u16,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn push(&mut self, severity: Severity, message: String) {
+        let now = Instant::now();
+        let toast_until = (!self.log_visible).then_some(now + TOAST_DURATION);
+        if let Some(last) = self.notices.front_mut()
+            && last.severity == severity
+            && last.message == message
+        {
+            last.repeats += 1;
+            last.received = now;
+            last.toast_until = toast_until;
+            return;
+        }
+        self.notices.push_front(Notice {
+            severity,
+            message,
+            repeats: 1,
+            received: now,
+            toast_until,
+        });
+        self.notices.truncate(MAX_LOG);
+    }
+    fn toasts(&self) -> impl Iterator<Item = &Notice> {
+        let now = Instant::now();
+        self.notices
+            .iter()
+            .filter(move |notice| notice.toast_until.is_some_and(|until| now < until))
+            .take(MAX_TOASTS)
+    }
+    // Toasts whose time is up but that were on screen at the last frame.
+    fn has_expired_toasts(&self) -> bool {
+        let now = Instant::now();
+        self.notices
+            .iter()
+            .any(|notice| notice.toast_until.is_some_and(|until| until <= now))
+    }
+    fn show_log(&mut self) {
+        self.log_visible = true;
+        self.scroll = 0;
+        // everything is in the log now
+        for notice in self.notices.iter_mut() {
+            notice.toast_until = None;
+        }
+    }
+    fn draw_toasts(&self, frame: &mut Frame, area: Rect) {
+        let width = TOAST_WIDTH.min(area.width);
+        let mut y = area.y;
+        for notice in self.toasts() {
+            let text = notice.text();
+            let inner = width.saturating_sub(2).max(1) as usize;
+            let lines = Span::raw(&text).width().div_ceil(inner).clamp(1, 3) as u16;
+            let height = (lines + 2).min(area.bottom().saturating_sub(y));
+            if height < 3 {
+                break;
+            }
+            let rect = Rect::new(area.right() - width, y, width, height);
+            let toast = Paragraph::new(text)
+                .wrap(Wrap { trim: true })
+                .style(notice.severity.style())
+                .block(Block::bordered().title(notice.severity.to_string()));
+            frame.render_widget(Clear, rect);
+            frame.render_widget(toast, rect);
+            y += height;
+        }
+    }
+    fn draw_log(&self, frame: &mut Frame, area: Rect) {
+        frame.render_widget(Clear, area);
+        let lines: Vec<Line> = if self.notices.is_empty() {
+            vec![Line::from("Nothing has gone wrong so far.")]
+        } else {
+            self.notices
+                .iter()
+                .map(|notice| {
+                    Line::from(vec![
+                        Span::raw(format!("{:>8}  ", age(notice.received.elapsed()))),
+                        Span::styled(format!(" {} ", notice.severity), notice.severity.style()),
+                        Span::raw(format!(" {}", notice.text())),
+                    ])
+                })
+                .collect()
+        };
+        let log = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(
+                Block::bordered()
+                    .title("Errors and warnings")
+                    .title_bottom(Line::from(" j/k: scroll  q: close ").right_aligned())
+                    .bold(),
+            );
+        frame.render_widget(log, area);
+    }
+}
+
+// How long ago something happened, roughly, e.g. "12s ago".
+fn age(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
+impl Component for Notifications {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Error(message) => self.push(Severity::Error, message),
+            Action::Warning(message) => self.push(Severity::Warning, message),
+            Action::Tick if self.has_expired_toasts() => {
+                let now = Instant::now();
+                for notice in self.notices.iter_mut() {
+                    notice.toast_until = notice.toast_until.filter(|&until| now < until);
+                }
+            }
+            Action::ShowLog => self.show_log(),
+            Action::ShowGame => self.log_visible = false,
+            Action::ScrollDown if self.log_visible => {
+                let last = self.notices.len().saturating_sub(1) as u16;
+                self.scroll = (self.scroll + 1).min(last);
+            }
+            Action::ScrollUp if self.log_visible => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn changes_on_tick(&self) -> bool {
+        self.has_expired_toasts()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if self.log_visible {
+            self.draw_log(frame, area);
+        } else {
+            self.draw_toasts(frame, area);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn screen(notifications: &mut Notifications) -> Vec<String> {
+        let mut terminal = Terminal::new(backend::TestBackend::new(60, 8)).unwrap();
+        terminal
+            .draw(|frame| notifications.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..8)
+            .map(|y| (0..60).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_toasts() {
+        let mut notifications = Notifications::new();
+        notifications
+            .update(Action::Error("could not save stats.json".into()))
+            .unwrap();
+        notifications
+            .update(Action::Error("could not save stats.json".into()))
+            .unwrap();
+        notifications
+            .update(Action::Warning("config not reloaded".into()))
+            .unwrap();
+        assert_eq!(notifications.notices.len(), 2);
+        let lines = screen(&mut notifications);
+        // the newest on top, in the right 50 columns
+        assert!(lines[0][10..].starts_with("┌warning"));
+        assert!(lines[1].contains("config not reloaded"));
+        assert!(lines[4].contains("could not save stats.json (2x)"));
+
+        assert!(!notifications.changes_on_tick());
+        notifications.notices[0].toast_until = Some(Instant::now());
+        assert_eq!(notifications.toasts().count(), 1);
+        // the next tick takes it off the screen
+        assert!(notifications.changes_on_tick());
+        notifications.update(Action::Tick).unwrap();
+        assert!(!notifications.changes_on_tick());
+        assert_eq!(notifications.toasts().count(), 1);
+        // opening the log hides them all
+        notifications.update(Action::ShowLog).unwrap();
+        assert_eq!(notifications.toasts().count(), 0);
+    }
+
+    #[test]
+    fn test_log() {
+        let mut notifications = Notifications::new();
+        notifications.update(Action::ShowLog).unwrap();
+        assert!(screen(&mut notifications)[1].contains("Nothing has gone wrong"));
+        for i in 0..3 {
+            notifications
+                .update(Action::Warning(format!("warning {i}")))
+                .unwrap();
+        }
+        // nothing pops up while the log is open
+        assert_eq!(notifications.toasts().count(), 0);
+        let lines = screen(&mut notifications);
+        assert!(lines[1].contains("0s ago   warning  warning 2"));
+        assert!(lines[3].contains("warning 0"));
+
+        for _ in 0..5 {
+            notifications.update(Action::ScrollDown).unwrap();
+        }
+        assert_eq!(notifications.scroll, 2);
+        notifications.update(Action::ScrollUp).unwrap();
+        assert!(screen(&mut notifications)[1].contains("warning 1"));
+        notifications.update(Action::ShowGame).unwrap();
+        assert!(!notifications.log_visible);
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age(Duration::from_secs(12)), "12s ago");
+        assert_eq!(age(Duration::from_secs(150)), "2m ago");
+        assert_eq!(age(Duration::from_secs(7300)), "2h ago");
+    }
+}
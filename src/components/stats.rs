@@ -0,0 +1,454 @@
+use super::Component;
+use crate::{
+    action::{Action, GameRecord},
+    components::lib::daily::Daily,
+    config::get_data_dir,
+};
+use color_eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use tokio::sync::mpsc::UnboundedSender;
+
+// Bars in the histogram of winning times.
+const HISTOGRAM_BUCKETS: u64 = 10;
+
+/// Results for one kind of board.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetStats {
+    pub played: u64,
+    pub won: u64,
+    // time of every won game, in milliseconds
+    pub times: Vec<u64>,
+}
+
+impl PresetStats {
+    pub fn best_millis(&self) -> Option<u64> {
+        self.times.iter().copied().min()
+    }
+    pub fn average_millis(&self) -> Option<u64> {
+        match self.times.len() {
+            0 => None,
+            n => Some(self.times.iter().sum::<u64>() / n as u64),
+        }
+    }
+    // Number of wins per time range, as (start of range in seconds, count).
+    pub fn histogram(&self) -> Vec<(u64, u64)> {
+        let (Some(min), Some(max)) = (self.times.iter().min(), self.times.iter().max()) else {
+            return vec![];
+        };
+        let (min, max) = (min / 1000, max / 1000);
+        let width = ((max - min) / HISTOGRAM_BUCKETS + 1).max(1);
+        let buckets = (max - min) / width + 1;
+        let mut counts = vec![0; buckets as usize];
+        for t in &self.times {
+            counts[((t / 1000 - min) / width) as usize] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (min + i as u64 * width, count))
+            .collect()
+    }
+}
+
+/// Results on the daily board of one date.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub attempts: u64,
+    // fastest win, None until the board has been cleared
+    pub best_millis: Option<u64>,
+}
+
+/// Lifetime results, kept in the data directory.
+///
+/// Daily boards are kept apart and do not count towards the other numbers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statistics {
+    pub played: u64,
+    pub won: u64,
+    pub lost: u64,
+    pub current_streak: u64,
+    pub best_streak: u64,
+    pub presets: BTreeMap<String, PresetStats>,
+    #[serde(default)]
+    pub daily: BTreeMap<String, DailyStats>,
+}
+
+impl Statistics {
+    pub fn path() -> PathBuf {
+        get_data_dir().join("stats.json")
+    }
+    // Read the statistics, starting over if there are none yet.
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(get_data_dir())?;
+        fs::write(Self::path(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+    pub fn record(&mut self, record: &GameRecord) {
+        if let Some(date) = &record.daily {
+            let daily = self.daily.entry(date.clone()).or_default();
+            daily.attempts += 1;
+            if record.won {
+                daily.best_millis = Some(
+                    daily
+                        .best_millis
+                        .map_or(record.millis, |best| best.min(record.millis)),
+                );
+            }
+            return;
+        }
+        let preset = self.presets.entry(record.preset.clone()).or_default();
+        self.played += 1;
+        preset.played += 1;
+        if record.won {
+            self.won += 1;
+            preset.won += 1;
+            preset.times.push(record.millis);
+            self.current_streak += 1;
+            self.best_streak = self.best_streak.max(self.current_streak);
+        } else {
+            self.lost += 1;
+            self.current_streak = 0;
+        }
+    }
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            return 0.0;
+        }
+        self.won as f64 / self.played as f64
+    }
+}
+
+fn format_millis(millis: Option<u64>) -> String {
+    match millis {
+        Some(millis) => format!("{:.1}s", millis as f64 / 1000.0),
+        None => "-".to_string(),
+    }
+}
+
+#[derive(Default)]
+pub struct Stats {
+    statistics: Statistics,
+    visible: bool,
+    // preset whose times are shown in the histogram
+    selected: usize,
+    confirm_reset: bool,
+    // why the statistics could not be read, to be reported once actions can be sent
+    load_error: Option<String>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        let mut load_error = None;
+        let statistics = Statistics::load().unwrap_or_else(|e| {
+            // keep the broken file around instead of overwriting it with the next result
+            let path = Statistics::path();
+            let backup = path.with_extension("json.bak");
+            let _ = fs::rename(&path, &backup);
+            load_error = Some(format!(
+                "could not read {}, starting over and keeping it as {}: {e}",
+                path.display(),
+                backup.display()
+            ));
+            Statistics::default()
+        });
+        Self {
+            statistics,
+            load_error,
+            ..Default::default()
+        }
+    }
+    fn save(&self) -> Option<Action> {
+        let e = self.statistics.save().err()?;
+        Some(Action::Error(format!(
+            "could not save {}: {e}",
+            Statistics::path().display()
+        )))
+    }
+    fn select(&mut self, step: isize) {
+        let len = self.statistics.presets.len();
+        if len > 0 {
+            self.selected = (self.selected as isize + step).rem_euclid(len as isize) as usize;
+        }
+    }
+    fn summary(&self) -> Vec<Line<'static>> {
+        let s = &self.statistics;
+        vec![
+            Line::from(format!(
+                "Played: {}   Won: {}   Lost: {}   Win rate: {:.0}%",
+                s.played,
+                s.won,
+                s.lost,
+                s.win_rate() * 100.0
+            )),
+            Line::from(format!(
+                "Current streak: {}   Best streak: {}",
+                s.current_streak, s.best_streak
+            )),
+            Line::from(format!(
+                "Today's daily: {}   Dailies cleared: {}",
+                match s.daily.get(&Daily::today().date) {
+                    Some(DailyStats {
+                        best_millis: Some(millis),
+                        ..
+                    }) => format!("cleared in {}", format_millis(Some(*millis))),
+                    Some(daily) => format!("not cleared after {} attempts", daily.attempts),
+                    None => "not played yet".to_string(),
+                },
+                s.daily.values().filter(|d| d.best_millis.is_some()).count()
+            )),
+        ]
+    }
+    fn preset_table(&self) -> Table<'static> {
+        let rows = self
+            .statistics
+            .presets
+            .iter()
+            .enumerate()
+            .map(|(i, (name, preset))| {
+                let row = Row::new(vec![
+                    name.clone(),
+                    preset.played.to_string(),
+                    preset.won.to_string(),
+                    format_millis(preset.average_millis()),
+                    format_millis(preset.best_millis()),
+                ]);
+                if i == self.selected {
+                    row.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    row
+                }
+            });
+        Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(10),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(vec!["Preset", "Played", "Won", "Average", "Best"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title("Per preset"))
+    }
+    fn histogram(&self) -> BarChart<'static> {
+        let selected = self.statistics.presets.iter().nth(self.selected);
+        let title = match selected {
+            Some((name, _)) => format!("Winning times: {name}"),
+            None => "Winning times".to_string(),
+        };
+        let bars: Vec<Bar> = selected
+            .map(|(_, preset)| preset.histogram())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(start, count)| {
+                Bar::default()
+                    .value(count)
+                    .label(Line::from(format!("{start}s")))
+            })
+            .collect();
+        BarChart::default()
+            .block(Block::bordered().title(title))
+            .bar_width(6)
+            .bar_gap(1)
+            .data(BarGroup::default().bars(&bars))
+    }
+}
+
+impl Component for Stats {
+    fn register_action_handler(&mut self, tx: UnboundedSender<Action>) -> Result<()> {
+        if let Some(message) = self.load_error.take() {
+            tx.send(Action::Warning(message))?;
+        }
+        Ok(())
+    }
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::GameFinished(record) => {
+                self.statistics.record(&record);
+                return Ok(self.save());
+            }
+            Action::ShowStats => self.visible = true,
+            Action::ShowGame => {
+                self.visible = false;
+                self.confirm_reset = false;
+            }
+            Action::NextPreset if self.visible => self.select(1),
+            Action::PreviousPreset if self.visible => self.select(-1),
+            Action::ResetStats if self.visible => self.confirm_reset = true,
+            Action::Confirm if self.confirm_reset => {
+                self.statistics = Statistics::default();
+                self.selected = 0;
+                self.confirm_reset = false;
+                return Ok(self.save());
+            }
+            Action::Cancel => self.confirm_reset = false,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if !self.visible {
+            return Ok(());
+        }
+        frame.render_widget(Clear, area);
+        let block = Block::bordered().title("Statistics").bold();
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(4),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+        frame.render_widget(
+            Paragraph::new(self.summary()).alignment(Alignment::Center),
+            layout[0],
+        );
+        frame.render_widget(self.preset_table(), layout[1]);
+        frame.render_widget(self.histogram(), layout[2]);
+        frame.render_widget(
+            Paragraph::new("←/→: preset   'R': reset   'q': back to the game")
+                .alignment(Alignment::Center),
+            layout[3],
+        );
+
+        if self.confirm_reset {
+            let popup = Rect::new(
+                area.x + area.width.saturating_sub(40) / 2,
+                area.y + area.height.saturating_sub(5) / 2,
+                area.width.min(40),
+                area.height.min(5),
+            );
+            frame.render_widget(Clear, popup);
+            frame.render_widget(
+                Paragraph::new("Reset all statistics?\n\n'y': yes   'n': no")
+                    .alignment(Alignment::Center)
+                    .block(Block::bordered().fg(Color::Red)),
+                popup,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(preset: &str, won: bool, millis: u64) -> GameRecord {
+        GameRecord {
+            preset: preset.to_string(),
+            won,
+            millis,
+            daily: None,
+        }
+    }
+
+    #[test]
+    fn test_daily_kept_apart() {
+        let mut statistics = Statistics::default();
+        let daily = |won, millis| GameRecord {
+            daily: Some("2026-10-18".to_string()),
+            ..record("daily", won, millis)
+        };
+        statistics.record(&daily(false, 3_000));
+        statistics.record(&daily(true, 50_000));
+        statistics.record(&daily(true, 40_000));
+        statistics.record(&daily(true, 45_000));
+        assert_eq!(
+            statistics.daily["2026-10-18"],
+            DailyStats {
+                attempts: 4,
+                best_millis: Some(40_000)
+            }
+        );
+        assert_eq!(statistics.played, 0);
+        assert!(statistics.presets.is_empty());
+        // files written before daily boards existed still load
+        let old =
+            r#"{"played":1,"won":1,"lost":0,"current_streak":1,"best_streak":1,"presets":{}}"#;
+        assert!(
+            serde_json::from_str::<Statistics>(old)
+                .unwrap()
+                .daily
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_streaks() {
+        let mut statistics = Statistics::default();
+        for won in [true, true, false, true, true, true, false, true] {
+            statistics.record(&record("beginner", won, 10_000));
+        }
+        assert_eq!(statistics.played, 8);
+        assert_eq!(statistics.won, 6);
+        assert_eq!(statistics.lost, 2);
+        assert_eq!(statistics.current_streak, 1);
+        assert_eq!(statistics.best_streak, 3);
+        assert_eq!(statistics.win_rate(), 0.75);
+    }
+
+    #[test]
+    fn test_preset_times() {
+        let mut statistics = Statistics::default();
+        statistics.record(&record("beginner", true, 12_000));
+        statistics.record(&record("beginner", true, 8_000));
+        statistics.record(&record("beginner", false, 1_000));
+        statistics.record(&record("expert", true, 95_000));
+        let beginner = &statistics.presets["beginner"];
+        assert_eq!(beginner.played, 3);
+        assert_eq!(beginner.won, 2);
+        assert_eq!(beginner.best_millis(), Some(8_000));
+        assert_eq!(beginner.average_millis(), Some(10_000));
+        assert_eq!(statistics.presets["expert"].best_millis(), Some(95_000));
+        assert_eq!(PresetStats::default().average_millis(), None);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let preset = PresetStats {
+            played: 5,
+            won: 5,
+            times: vec![10_000, 10_500, 14_999, 35_000, 41_000],
+        };
+        // 10s to 41s in buckets of 4 seconds
+        let histogram = preset.histogram();
+        assert_eq!(histogram.len(), 8);
+        assert_eq!(histogram[0], (10, 2));
+        assert_eq!(histogram[1], (14, 1));
+        assert_eq!(histogram[6], (34, 1));
+        assert_eq!(histogram[7], (38, 1));
+        assert_eq!(histogram.iter().map(|(_, c)| c).sum::<u64>(), 5);
+        assert!(PresetStats::default().histogram().is_empty());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut statistics = Statistics::default();
+        statistics.record(&record("9x9/10", true, 5_000));
+        let json = serde_json::to_string(&statistics).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Statistics>(&json).unwrap(),
+            statistics
+        );
+    }
+}
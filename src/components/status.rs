@@ -0,0 +1,134 @@
+use super::Component;
+use crate::action::Action;
+use color_eyre::Result;
+use ratatui::{prelude::*, widgets::*};
+use std::time::{Duration, Instant};
+
+// How long an informational message stays on screen.
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+
+/// A line at the bottom of the screen for short messages, shown over whatever is there.
+#[derive(Debug, Default)]
+pub struct StatusLine {
+    message: Option<String>,
+    is_error: bool,
+    // when the message was shown; errors stay until replaced
+    shown_at: Option<Instant>,
+    // keys of an incomplete sequence, shown at the right end
+    pending_keys: String,
+}
+
+impl StatusLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn is_expired(&self) -> bool {
+        self.shown_at
+            .is_some_and(|shown_at| shown_at.elapsed() >= MESSAGE_DURATION)
+    }
+    fn show(&mut self, message: String, is_error: bool) {
+        self.message = Some(message);
+        self.is_error = is_error;
+        self.shown_at = (!is_error).then(Instant::now);
+    }
+}
+
+impl Component for StatusLine {
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Status(message) => self.show(message, false),
+            Action::StatusError(message) => self.show(message, true),
+            Action::PendingKeys(keys) => self.pending_keys = keys,
+            Action::Tick if self.is_expired() => {
+                self.message = None;
+                self.shown_at = None;
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn changes_on_tick(&self) -> bool {
+        self.is_expired()
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        if area.height == 0 {
+            return Ok(());
+        }
+        let line = Rect {
+            y: area.bottom() - 1,
+            height: 1,
+            ..area
+        };
+        if let Some(message) = &self.message {
+            let style = if self.is_error {
+                Style::default().fg(Color::White).bg(Color::Red)
+            } else {
+                Style::default().fg(Color::Black).bg(Color::Gray)
+            };
+            frame.render_widget(Clear, line);
+            frame.render_widget(Paragraph::new(message.as_str()).style(style), line);
+        }
+        if !self.pending_keys.is_empty() {
+            let text = format!(" {} ", self.pending_keys);
+            let width = (Span::raw(&text).width() as u16).min(line.width);
+            let keys = Rect {
+                x: line.right() - width,
+                width,
+                ..line
+            };
+            frame.render_widget(Clear, keys);
+            frame.render_widget(
+                Paragraph::new(text).style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+                keys,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_messages_expire() {
+        let mut status = StatusLine::new();
+        status
+            .update(Action::Status("configuration reloaded".into()))
+            .unwrap();
+        status.update(Action::Tick).unwrap();
+        assert_eq!(status.message.as_deref(), Some("configuration reloaded"));
+        assert!(!status.changes_on_tick());
+
+        status.shown_at = Instant::now().checked_sub(MESSAGE_DURATION);
+        assert!(status.changes_on_tick());
+        status.update(Action::Tick).unwrap();
+        assert_eq!(status.message, None);
+        assert!(!status.changes_on_tick());
+
+        // errors stay until something else is shown
+        status.update(Action::StatusError("broken".into())).unwrap();
+        status.update(Action::Tick).unwrap();
+        assert_eq!(status.message.as_deref(), Some("broken"));
+        status.update(Action::Status("fixed".into())).unwrap();
+        assert!(!status.is_error);
+    }
+
+    #[test]
+    fn test_pending_keys() {
+        let mut status = StatusLine::new();
+        status.update(Action::PendingKeys("5g".into())).unwrap();
+        let mut terminal = Terminal::new(backend::TestBackend::new(20, 3)).unwrap();
+        terminal
+            .draw(|frame| status.draw(frame, frame.area()).unwrap())
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let line: String = (0..20).map(|x| buffer[(x, 2)].symbol()).collect();
+        assert_eq!(line, "                 5g ");
+
+        status.update(Action::PendingKeys(String::new())).unwrap();
+        assert!(status.pending_keys.is_empty());
+    }
+}
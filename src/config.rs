@@ -1,27 +1,229 @@
-use std::{collections::HashMap, env, path::PathBuf};
-
-use color_eyre::Result;
+use std::{
+    collections::HashMap,
+    env, fmt,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::{Result, eyre::eyre};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
 use directories::ProjectDirs;
 use lazy_static::lazy_static;
 use ratatui::style::{Color, Modifier, Style};
 use serde::{Deserialize, de::Deserializer};
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::error;
 
-use crate::{action::Action, app::Mode};
+use crate::{
+    action::Action,
+    app::Mode,
+    cli::{MAX_DINOS_PER_FIELD, MAX_HEIGHT, MAX_WIDTH, MIN_SIZE},
+    components::{
+        game::{Preset, Theme},
+        lib::dinomite::Charset,
+    },
+    tui::MAX_RATE,
+};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
-#[derive(Clone, Debug, Deserialize, Default)]
-pub struct AppConfig {}
+/// Defaults for new games from the `game` section, overridden by command line arguments.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub num_dinos: Option<usize>,
+    // board size used unless width, height or the number of dinos are given
+    pub preset: Option<Preset>,
+    pub safe_start: Option<bool>,
+    pub charset: Option<Charset>,
+    pub theme: Option<Theme>,
+    pub mouse: Option<bool>,
+    pub question_marks: Option<bool>,
+    pub player_name: Option<String>,
+    pub labels: Option<bool>,
+}
+
+impl GameConfig {
+    // Settings of other take precedence.
+    fn merge(&mut self, other: GameConfig) {
+        *self = GameConfig {
+            width: other.width.or(self.width),
+            height: other.height.or(self.height),
+            num_dinos: other.num_dinos.or(self.num_dinos),
+            preset: other.preset.or(self.preset),
+            safe_start: other.safe_start.or(self.safe_start),
+            charset: other.charset.or(self.charset),
+            theme: other.theme.or(self.theme),
+            mouse: other.mouse.or(self.mouse),
+            question_marks: other.question_marks.or(self.question_marks),
+            player_name: other.player_name.or(self.player_name.take()),
+            labels: other.labels.or(self.labels),
+        };
+    }
+    // Report values out of range as diagnostics for file and drop them.
+    fn validate(&mut self, file: &Path, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        let mut report = |key: &str, reason: String| {
+            diagnostics.push(ConfigDiagnostic {
+                file: file.to_path_buf(),
+                mode: None,
+                key: Some(format!("game.{key}")),
+                reason,
+            });
+        };
+        if let Some(width) = self.width.take_if(|w| !(MIN_SIZE..=MAX_WIDTH).contains(w)) {
+            report(
+                "width",
+                format!("{width} is not between {MIN_SIZE} and {MAX_WIDTH}"),
+            );
+        }
+        if let Some(height) = self
+            .height
+            .take_if(|h| !(MIN_SIZE..=MAX_HEIGHT).contains(h))
+        {
+            report(
+                "height",
+                format!("{height} is not between {MIN_SIZE} and {MAX_HEIGHT}"),
+            );
+        }
+        let max_dinos = MAX_WIDTH * MAX_HEIGHT * MAX_DINOS_PER_FIELD as usize;
+        if let Some(num_dinos) = self.num_dinos.take_if(|n| *n > max_dinos) {
+            report("num_dinos", format!("{num_dinos} is more than {max_dinos}"));
+        }
+        if self
+            .player_name
+            .take_if(|name| name.trim().is_empty() || name.chars().any(char::is_control))
+            .is_some()
+        {
+            report(
+                "player_name",
+                "must not be blank or contain control characters".to_string(),
+            );
+        }
+    }
+}
+
+/// Update and redraw rates from the `display` section, overridden by command line arguments.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub tick_rate: Option<f64>,
+    pub frame_rate: Option<f64>,
+    pub render_on_change: Option<bool>,
+}
+
+impl DisplayConfig {
+    // Settings of other take precedence.
+    fn merge(&mut self, other: DisplayConfig) {
+        *self = DisplayConfig {
+            tick_rate: other.tick_rate.or(self.tick_rate),
+            frame_rate: other.frame_rate.or(self.frame_rate),
+            render_on_change: other.render_on_change.or(self.render_on_change),
+        };
+    }
+    // Report rates out of range as diagnostics for file and drop them.
+    fn validate(&mut self, file: &Path, diagnostics: &mut Vec<ConfigDiagnostic>) {
+        for (key, rate) in [
+            ("tick_rate", &mut self.tick_rate),
+            ("frame_rate", &mut self.frame_rate),
+        ] {
+            if let Some(value) = rate.take_if(|r| !(*r > 0.0 && *r <= MAX_RATE)) {
+                diagnostics.push(ConfigDiagnostic {
+                    file: file.to_path_buf(),
+                    mode: None,
+                    key: Some(format!("display.{key}")),
+                    reason: format!("{value} is not more than 0 and at most {MAX_RATE}"),
+                });
+            }
+        }
+    }
+}
 
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default)]
 pub struct Config {
-    #[serde(default)]
     pub keybindings: KeyBindings,
-    #[serde(default)]
     pub styles: Styles,
+    pub game: GameConfig,
+    pub display: DisplayConfig,
+    // how long to wait for the rest of a key sequence, if configured
+    pub key_timeout: Option<Duration>,
+}
+
+// Files read from the config directory, in the order they are applied.
+const CONFIG_FILES: [(&str, config::FileFormat); 5] = [
+    ("config.json5", config::FileFormat::Json5),
+    ("config.json", config::FileFormat::Json),
+    ("config.yaml", config::FileFormat::Yaml),
+    ("config.toml", config::FileFormat::Toml),
+    ("config.ini", config::FileFormat::Ini),
+];
+
+// How long to wait for the next key of a sequence unless configured otherwise.
+const DEFAULT_KEY_TIMEOUT: Duration = Duration::from_secs(1);
+
+// How often the config files are checked for changes while the game runs.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+// Where the built-in defaults come from, for diagnostics.
+const DEFAULT_CONFIG_FILE: &str = "built-in defaults";
+
+/// A problem found while reading the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    pub file: PathBuf,
+    pub mode: Option<Mode>,
+    // the keys as written in the file
+    pub key: Option<String>,
+    pub reason: String,
+}
+
+impl ConfigDiagnostic {
+    // A problem with the file as a whole.
+    fn file(file: &Path, reason: String) -> Self {
+        ConfigDiagnostic {
+            file: file.to_path_buf(),
+            mode: None,
+            key: None,
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(mode) = &self.mode {
+            write!(f, ", mode {mode:?}")?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ", key `{key}`")?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// What was read while loading the configuration, and what was wrong with it.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReport {
+    // files found in the config directory, in the order they were applied
+    pub files: Vec<PathBuf>,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+// Key bindings and styles as written in a single file, before the keys are parsed.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    keybindings: HashMap<Mode, HashMap<String, Action>>,
+    #[serde(default)]
+    styles: Styles,
+    #[serde(default)]
+    game: GameConfig,
+    #[serde(default)]
+    display: DisplayConfig,
+    key_timeout_ms: Option<u64>,
 }
 
 lazy_static! {
@@ -37,43 +239,64 @@ lazy_static! {
 }
 
 impl Config {
-    pub fn new() -> Result<Self, config::ConfigError> {
-        let default_config: Config = json5::from_str(CONFIG).unwrap();
-        let data_dir = get_data_dir();
-        let config_dir = get_config_dir();
-        let mut builder = config::Config::builder()
-            .set_default("data_dir", data_dir.to_str().unwrap())?
-            .set_default("config_dir", config_dir.to_str().unwrap())?;
-
-        let config_files = [
-            ("config.json5", config::FileFormat::Json5),
-            ("config.json", config::FileFormat::Json),
-            ("config.yaml", config::FileFormat::Yaml),
-            ("config.toml", config::FileFormat::Toml),
-            ("config.ini", config::FileFormat::Ini),
-        ];
-        let mut found_config = false;
-        for (file, format) in &config_files {
-            let source = config::File::from(config_dir.join(file))
-                .format(*format)
-                .required(false);
-            builder = builder.add_source(source);
-            if config_dir.join(file).exists() {
-                found_config = true
+    // Load the configuration, failing with all problems found if there are any.
+    pub fn new() -> Result<Self> {
+        let (config, report) = Self::load(&get_config_dir());
+        if !report.diagnostics.is_empty() {
+            let problems: Vec<String> = report.diagnostics.iter().map(|d| d.to_string()).collect();
+            return Err(eyre!(
+                "invalid configuration:\n{}\n(run `{} config check` to check it again)",
+                problems.join("\n"),
+                env!("CARGO_PKG_NAME")
+            ));
+        }
+        Ok(config)
+    }
+
+    // Read the config files in config_dir on top of the built-in defaults. Problems are
+    // collected in the report instead of stopping at the first one; whatever could be
+    // read is still applied.
+    pub fn load(config_dir: &Path) -> (Self, ConfigReport) {
+        let mut report = ConfigReport::default();
+        let default_file = Path::new(DEFAULT_CONFIG_FILE);
+        let default_config = match json5::from_str::<ConfigFile>(CONFIG) {
+            Ok(file) => file.parse(default_file, &mut report.diagnostics),
+            Err(e) => {
+                report
+                    .diagnostics
+                    .push(ConfigDiagnostic::file(default_file, e.to_string()));
+                Config::default()
+            }
+        };
+
+        let mut cfg = Config::default();
+        for (file, format) in &CONFIG_FILES {
+            let path = config_dir.join(file);
+            if !path.exists() {
+                continue;
+            }
+            report.files.push(path.clone());
+            match ConfigFile::read(&path, *format) {
+                // later files take precedence
+                Ok(file) => cfg.merge(file.parse(&path, &mut report.diagnostics)),
+                Err(e) => report.diagnostics.push(ConfigDiagnostic::file(&path, e)),
             }
         }
-        if !found_config {
+        if report.files.is_empty() {
             error!("No configuration file found. Application may not behave as expected");
         }
 
-        let mut cfg: Self = builder.build()?.try_deserialize()?;
-
         for (mode, default_bindings) in default_config.keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
-            for (key, cmd) in default_bindings.iter() {
-                user_bindings
-                    .entry(key.clone())
-                    .or_insert_with(|| cmd.clone());
+            let user_keys: Vec<_> = user_bindings.keys().cloned().collect();
+            for (keys, cmd) in default_bindings.iter() {
+                // a default that would shadow a user binding or be shadowed by it gives way
+                if !user_keys
+                    .iter()
+                    .any(|user| user.starts_with(keys) || keys.starts_with(user))
+                {
+                    user_bindings.insert(keys.clone(), cmd.clone());
+                }
             }
         }
         for (mode, default_styles) in default_config.styles.iter() {
@@ -82,31 +305,174 @@ impl Config {
                 user_styles.entry(style_key.clone()).or_insert(*style);
             }
         }
+        let mut game = default_config.game;
+        game.merge(cfg.game);
+        cfg.game = game;
+        let mut display = default_config.display;
+        display.merge(cfg.display);
+        cfg.display = display;
+        cfg.key_timeout = cfg.key_timeout.or(default_config.key_timeout);
+
+        (cfg, report)
+    }
 
-        Ok(cfg)
+    fn merge(&mut self, other: Config) {
+        for (mode, bindings) in other.keybindings.0 {
+            self.keybindings.entry(mode).or_default().extend(bindings);
+        }
+        for (mode, styles) in other.styles.0 {
+            self.styles.entry(mode).or_default().extend(styles);
+        }
+        self.game.merge(other.game);
+        self.display.merge(other.display);
+        self.key_timeout = other.key_timeout.or(self.key_timeout);
+    }
+    pub fn key_timeout(&self) -> Duration {
+        self.key_timeout.unwrap_or(DEFAULT_KEY_TIMEOUT)
+    }
+}
+
+impl ConfigFile {
+    // JSON is read like the built-in defaults, as the config crate would lowercase the keys
+    // reported in diagnostics.
+    fn read(path: &Path, format: config::FileFormat) -> Result<Self, String> {
+        if matches!(format, config::FileFormat::Json5 | config::FileFormat::Json) {
+            let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            return json5::from_str(&text).map_err(|e| e.to_string());
+        }
+        config::Config::builder()
+            .add_source(config::File::from(path).format(format))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .map_err(|e| e.to_string())
+    }
+
+    // Parse the key bindings, reporting keys that cannot be parsed, different keys written
+    // for the same key bound to different actions, and sequences that can never be typed
+    // because their beginning is bound on its own.
+    fn parse(mut self, file: &Path, diagnostics: &mut Vec<ConfigDiagnostic>) -> Config {
+        self.game.validate(file, diagnostics);
+        self.display.validate(file, diagnostics);
+        if self.key_timeout_ms == Some(0) {
+            diagnostics.push(ConfigDiagnostic {
+                file: file.to_path_buf(),
+                mode: None,
+                key: Some("key_timeout_ms".to_string()),
+                reason: "must be more than 0, or key sequences could never be typed".to_string(),
+            });
+        }
+        let mut modes: Vec<_> = self.keybindings.into_iter().collect();
+        // sorted, so that problems are reported in a stable order
+        modes.sort_by_key(|(mode, _)| format!("{mode:?}"));
+        let mut keybindings = KeyBindings::default();
+        for (mode, bindings) in modes {
+            let diagnostic = |key: &str, reason: String| ConfigDiagnostic {
+                file: file.to_path_buf(),
+                mode: Some(mode),
+                key: Some(key.to_string()),
+                reason,
+            };
+            let mut bindings: Vec<_> = bindings.into_iter().collect();
+            bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let mut parsed: Vec<(Vec<KeyEvent>, String, Action)> = vec![];
+            for (key, action) in bindings {
+                let keys = match parse_key_sequence(&key) {
+                    Ok(keys) => keys,
+                    Err(e) => {
+                        diagnostics.push(diagnostic(&key, e));
+                        continue;
+                    }
+                };
+                match parsed.iter().find(|(other, _, _)| *other == keys) {
+                    Some((_, other, other_action)) if *other_action != action => {
+                        diagnostics.push(diagnostic(
+                            &key,
+                            format!(
+                                "bound to {action}, but it is the same key as `{other}`, which is bound to {other_action}"
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                    None => parsed.push((keys, key, action)),
+                }
+            }
+            for (keys, key, action) in &parsed {
+                for (prefix, other, other_action) in &parsed {
+                    if prefix.len() < keys.len() && keys.starts_with(prefix) {
+                        diagnostics.push(diagnostic(
+                            key,
+                            format!(
+                                "bound to {action}, but can never be typed as `{other}` already triggers {other_action}"
+                            ),
+                        ));
+                    }
+                }
+            }
+            let bindings = parsed
+                .into_iter()
+                .map(|(keys, _, action)| (keys, action))
+                .collect();
+            keybindings.insert(mode, bindings);
+        }
+        Config {
+            keybindings,
+            styles: self.styles,
+            game: self.game,
+            display: self.display,
+            key_timeout: self
+                .key_timeout_ms
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis),
+        }
+    }
+}
+
+// Modification time and size of every config file in config_dir, None for missing ones.
+fn config_files_state(config_dir: &Path) -> Vec<Option<(SystemTime, u64)>> {
+    CONFIG_FILES
+        .iter()
+        .map(|(file, _)| {
+            let metadata = std::fs::metadata(config_dir.join(file)).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+// Send `Action::ReloadConfig` whenever a config file in config_dir is written, created or
+// removed, until nobody listens anymore.
+pub async fn watch(config_dir: PathBuf, tx: UnboundedSender<Action>) {
+    let mut last = config_files_state(&config_dir);
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let current = config_files_state(&config_dir);
+        if current != last {
+            last = current;
+            if tx.send(Action::ReloadConfig).is_err() {
+                break;
+            }
+        }
     }
 }
 
 pub fn get_data_dir() -> PathBuf {
-    let directory = if let Some(s) = DATA_FOLDER.clone() {
+    if let Some(s) = DATA_FOLDER.clone() {
         s
     } else if let Some(proj_dirs) = project_directory() {
         proj_dirs.data_local_dir().to_path_buf()
     } else {
         PathBuf::from(".").join(".data")
-    };
-    directory
+    }
 }
 
 pub fn get_config_dir() -> PathBuf {
-    let directory = if let Some(s) = CONFIG_FOLDER.clone() {
+    if let Some(s) = CONFIG_FOLDER.clone() {
         s
     } else if let Some(proj_dirs) = project_directory() {
         proj_dirs.config_local_dir().to_path_buf()
     } else {
         PathBuf::from(".").join(".config")
-    };
-    directory
+    }
 }
 
 fn project_directory() -> Option<ProjectDirs> {
@@ -116,28 +482,6 @@ fn project_directory() -> Option<ProjectDirs> {
 #[derive(Clone, Debug, Default, Deref, DerefMut)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Vec<KeyEvent>, Action>>);
 
-impl<'de> Deserialize<'de> for KeyBindings {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let parsed_map = HashMap::<Mode, HashMap<String, Action>>::deserialize(deserializer)?;
-
-        let keybindings = parsed_map
-            .into_iter()
-            .map(|(mode, inner_map)| {
-                let converted_inner_map = inner_map
-                    .into_iter()
-                    .map(|(key_str, cmd)| (parse_key_sequence(&key_str).unwrap(), cmd))
-                    .collect();
-                (mode, converted_inner_map)
-            })
-            .collect();
-
-        Ok(KeyBindings(keybindings))
-    }
-}
-
 fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
@@ -219,7 +563,6 @@ fn parse_key_code_with_modifiers(
     Ok(KeyEvent::new(c, modifiers))
 }
 
-#[allow(dead_code)]
 pub fn key_event_to_string(key_event: &KeyEvent) -> String {
     let char;
     let key_code = match key_event.code {
@@ -289,8 +632,8 @@ pub fn parse_key_sequence(raw: &str) -> Result<Vec<KeyEvent>, String> {
     }
     let raw = if !raw.contains("><") {
         let raw = raw.strip_prefix('<').unwrap_or(raw);
-        let raw = raw.strip_prefix('>').unwrap_or(raw);
-        raw
+
+        raw.strip_prefix('>').unwrap_or(raw)
     } else {
         raw
     };
@@ -506,6 +849,152 @@ mod tests {
         Ok(())
     }
 
+    fn diagnostics(json: &str) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = vec![];
+        json5::from_str::<ConfigFile>(json)
+            .unwrap()
+            .parse(Path::new("config.json5"), &mut diagnostics);
+        diagnostics
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let (_, report) = Config::load(Path::new("/nonexistent"));
+        assert!(report.files.is_empty());
+        assert_eq!(report.diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_invalid_key_reported() {
+        let diagnostics =
+            diagnostics(r#"{"keybindings": {"Game": {"<Ctlr-q>": "Quit", "x": "Look"}}}"#);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].mode, Some(Mode::Game));
+        assert_eq!(diagnostics[0].key.as_deref(), Some("<Ctlr-q>"));
+        assert!(
+            diagnostics[0]
+                .to_string()
+                .starts_with("config.json5, mode Game, key `<Ctlr-q>`: ")
+        );
+    }
+
+    #[test]
+    fn test_same_key_twice() {
+        let found = diagnostics(
+            r#"{"keybindings": {"Game": {"q": "Quit", "<q>": "Look", "<Q>": "Quit"}}}"#,
+        );
+        assert_eq!(found.len(), 1);
+        // keys are case insensitive, and the keys are checked in sorted order
+        assert_eq!(found[0].key.as_deref(), Some("<q>"));
+        assert!(found[0].reason.contains("`<Q>`"));
+        // the same action for both is harmless
+        assert_eq!(
+            diagnostics(r#"{"keybindings": {"Game": {"q": "Quit", "<q>": "Quit"}}}"#),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_shadowed_sequence() {
+        let diagnostics =
+            diagnostics(r#"{"keybindings": {"Stats": {"g": "Confirm", "<g><g>": "Cancel"}}}"#);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].mode, Some(Mode::Stats));
+        assert_eq!(diagnostics[0].key.as_deref(), Some("<g><g>"));
+    }
+
+    #[test]
+    fn test_load_user_file() {
+        let dir = env::temp_dir().join(format!("dinomite-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.json5");
+        std::fs::write(
+            &file,
+            r#"{"keybindings": {"Game": {"<x><x>": "Flag", "<Ctrl-!!>": "Quit"}}}"#,
+        )
+        .unwrap();
+        let (config, report) = Config::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.files, vec![file.clone()]);
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(report.diagnostics[0].file, file);
+        let game = config.keybindings.get(&Mode::Game).unwrap();
+        // the default for `x` would shadow the user's sequence
+        assert_eq!(game.get(&parse_key_sequence("x").unwrap()), None);
+        assert_eq!(
+            game.get(&parse_key_sequence("<x><x>").unwrap()),
+            Some(&Action::Flag)
+        );
+        assert_eq!(
+            game.get(&parse_key_sequence("q").unwrap()),
+            Some(&Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_game_section() {
+        let mut diagnostics = vec![];
+        let config = json5::from_str::<ConfigFile>(
+            r#"{"game": {"width": 2000, "height": 12, "preset": "expert", "charset": "ascii", "player_name": " "}}"#,
+        )
+        .unwrap()
+        .parse(Path::new("config.json5"), &mut diagnostics);
+        assert_eq!(
+            config.game,
+            GameConfig {
+                height: Some(12),
+                preset: Some(Preset::Expert),
+                charset: Some(Charset::Ascii),
+                ..Default::default()
+            }
+        );
+        let keys: Vec<_> = diagnostics.iter().map(|d| d.key.as_deref()).collect();
+        assert_eq!(keys, [Some("game.width"), Some("game.player_name")]);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "config.json5, key `game.width`: 2000 is not between 5 and 1000"
+        );
+        // misspelled settings are not silently ignored
+        assert!(json5::from_str::<ConfigFile>(r#"{"game": {"widht": 10}}"#).is_err());
+    }
+
+    #[test]
+    fn test_display_section() {
+        let mut diagnostics = vec![];
+        let config = json5::from_str::<ConfigFile>(
+            r#"{"display": {"tick_rate": 0, "frame_rate": 10.5, "render_on_change": true}}"#,
+        )
+        .unwrap()
+        .parse(Path::new("config.json5"), &mut diagnostics);
+        assert_eq!(
+            config.display,
+            DisplayConfig {
+                frame_rate: Some(10.5),
+                render_on_change: Some(true),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "config.json5, key `display.tick_rate`: 0 is not more than 0 and at most 1000"
+        );
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_config_files_state() {
+        let dir = env::temp_dir().join(format!("dinomite-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let before = config_files_state(&dir);
+        assert!(before.iter().all(Option::is_none));
+        std::fs::write(dir.join("config.toml"), "[keybindings]").unwrap();
+        let after = config_files_state(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_ne!(before, after);
+        assert!(after[3].is_some());
+    }
+
     #[test]
     fn test_simple_keys() {
         assert_eq!(
@@ -0,0 +1,247 @@
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::{
+    action::Action,
+    components::{
+        Component,
+        game::{Game, GameOptions, GameResult},
+        lib::dinomite::{FieldView, GameStatus, Position},
+    },
+};
+
+/// A line of the headless protocol, as sent by a bot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    // one of the actions on a field, with the field's x and y
+    Move(Action, usize, usize),
+    State,
+    Result,
+    Restart,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = color_eyre::Report;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let action = match words[..] {
+            ["state"] => return Ok(Command::State),
+            ["result"] => return Ok(Command::Result),
+            ["restart"] => return Ok(Command::Restart),
+            ["quit"] => return Ok(Command::Quit),
+            ["open", ..] => Action::Look,
+            ["flag", ..] => Action::Flag,
+            ["unflag", ..] => Action::Unflag,
+            ["chord", ..] => Action::Chord,
+            _ => return Err(eyre!("unknown command `{line}`")),
+        };
+        let [_, x, y] = words[..] else {
+            return Err(eyre!("`{}` takes a field as X Y", words[0]));
+        };
+        let coordinate = |c: &str| {
+            c.parse::<usize>()
+                .map_err(|_| eyre!("`{c}` is not a coordinate"))
+        };
+        Ok(Command::Move(action, coordinate(x)?, coordinate(y)?))
+    }
+}
+
+// Play a single game driven by commands read line by line from input, without a terminal
+// interface. Each move is answered on output with `ok` and the game status, `state` prints
+// the board, and the result is written as a line of JSON when the game is decided and when
+// input ends or says `quit`. Unreadable commands, and chords that would not uncover
+// anything, are answered with `error` and a reason.
+pub fn run(
+    options: GameOptions,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<GameResult> {
+    let mut game = Game::new(options);
+    game.update(Action::StartGame)?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.parse() {
+            Ok(Command::Quit) => break,
+            Ok(command) => execute(&mut game, command, &mut output)?,
+            Err(e) => writeln!(output, "error {e}")?,
+        }
+        output.flush()?;
+    }
+    let Some(Action::GameResult(result)) = game.update(Action::Quit)? else {
+        return Err(eyre!("game did not report a result"));
+    };
+    write_result(&result, &mut output)?;
+    output.flush()?;
+    Ok(result)
+}
+
+fn execute(game: &mut Game, command: Command, output: &mut impl Write) -> Result<()> {
+    match command {
+        Command::Move(action, x, y) => {
+            let (width, height) = (game.dinomite().get_width(), game.dinomite().get_height());
+            if x >= width || y >= height {
+                writeln!(
+                    output,
+                    "error {x} {y} is outside of the {width}x{height} board"
+                )?;
+                return Ok(());
+            }
+            let was_over = game.dinomite().is_game_over();
+            if action == Action::Chord && !was_over && !game.dinomite().can_chord(&Position(x, y)) {
+                writeln!(output, "error nothing to chord at {x} {y}")?;
+                return Ok(());
+            }
+            game.update(Action::MoveTo(x, y))?;
+            game.update(action)?;
+            writeln!(output, "ok {}", status(game.dinomite().status()))?;
+            if !was_over && game.dinomite().is_game_over() {
+                write_result(&game.result(), output)?;
+            }
+        }
+        Command::State => write_state(game, output)?,
+        Command::Result => write_result(&game.result(), output)?,
+        Command::Restart if game.dinomite().is_game_over() => {
+            game.update(Action::RestartGame)?;
+            game.update(Action::StartGame)?;
+            writeln!(output, "ok {}", status(game.dinomite().status()))?;
+        }
+        Command::Restart => writeln!(output, "error the game is not over yet")?,
+        Command::Quit => {}
+    }
+    Ok(())
+}
+
+fn status(status: &GameStatus) -> &'static str {
+    match status {
+        GameStatus::NotStarted => "notstarted",
+        GameStatus::Playing => "playing",
+        GameStatus::Won => "won",
+        GameStatus::Lost { .. } => "lost",
+    }
+}
+
+fn write_result(result: &GameResult, output: &mut impl Write) -> Result<()> {
+    writeln!(output, "result {}", serde_json::to_string(result)?)?;
+    Ok(())
+}
+
+// A header line `state STATUS WIDTH HEIGHT DINOS_LEFT`, followed by one line per row with
+// a token per field: `#` covered, `F` flagged (`F2` for two flags), `?` marked, `.` or a
+// number for uncovered fields, and once the game is over `*` for dinos (`*2` for two) and
+// `X` for the one that exploded.
+fn write_state(game: &Game, output: &mut impl Write) -> Result<()> {
+    let dinomite = game.dinomite();
+    writeln!(
+        output,
+        "state {} {} {} {}",
+        status(dinomite.status()),
+        dinomite.get_width(),
+        dinomite.get_height(),
+        dinomite.get_remaining_dinos()
+    )?;
+    for y in 0..dinomite.get_height() {
+        let row: Vec<String> = (0..dinomite.get_width())
+            .map(|x| token(dinomite.view(&Position(x, y))))
+            .collect();
+        writeln!(output, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+fn token(view: FieldView) -> String {
+    match view {
+        FieldView::Covered => "#".into(),
+        FieldView::Flagged(1) => "F".into(),
+        FieldView::Flagged(n) => format!("F{n}"),
+        FieldView::Revealed(0) => ".".into(),
+        FieldView::Revealed(n) => n.to_string(),
+        FieldView::Dino(1) => "*".into(),
+        FieldView::Dino(n) => format!("*{n}"),
+        FieldView::Exploded => "X".into(),
+        FieldView::Marked => "?".into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::game::Outcome;
+
+    fn play(board: &str, input: &str) -> (GameResult, Vec<String>) {
+        let options = GameOptions {
+            board: Some(board.parse().unwrap()),
+            ..Default::default()
+        };
+        let mut output = vec![];
+        let result = run(options, input.as_bytes(), &mut output).unwrap();
+        let lines = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        (result, lines)
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            "open 3 4".parse::<Command>().unwrap(),
+            Command::Move(Action::Look, 3, 4)
+        );
+        assert_eq!(
+            "  chord 0  1 ".parse::<Command>().unwrap(),
+            Command::Move(Action::Chord, 0, 1)
+        );
+        assert_eq!("state".parse::<Command>().unwrap(), Command::State);
+        assert!("open 3".parse::<Command>().is_err());
+        assert!("flag a 1".parse::<Command>().is_err());
+        assert!("state 1 2".parse::<Command>().is_err());
+        assert!("dig 1 2".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_win() {
+        let (result, lines) = play(
+            "*....\n.....\n.....\n.....\n.....",
+            "flag 0 0\nstate\n# uncovers everything else\nopen 4 4\nstate\n",
+        );
+        assert_eq!(result.outcome, Outcome::Won);
+        assert_eq!(lines[0], "ok notstarted");
+        assert_eq!(lines[1], "state notstarted 5 5 0");
+        assert_eq!(lines[2], "F # # # #");
+        assert_eq!(lines[7], "ok won");
+        assert!(lines[8].starts_with("result {\"outcome\":\"won\""));
+        assert_eq!(lines[9], "state won 5 5 0");
+        assert_eq!(lines[10], "F 1 . . .");
+        assert_eq!(lines[11], "1 1 . . .");
+        // the final result when the input ends
+        assert_eq!(lines[15], lines[8]);
+        assert_eq!(lines.len(), 16);
+    }
+
+    #[test]
+    fn test_loss_and_errors() {
+        let (result, lines) = play(
+            "*....\n*....\n.....\n.....\n.....",
+            "open 9 9\nrestart\nchord 0 0\nopen 0 0\nstate\nquit\nopen 4 4\n",
+        );
+        assert_eq!(result.outcome, Outcome::Lost);
+        assert_eq!(lines[0], "error 9 9 is outside of the 5x5 board");
+        assert_eq!(lines[1], "error the game is not over yet");
+        assert_eq!(lines[2], "error nothing to chord at 0 0");
+        assert_eq!(lines[3], "ok lost");
+        assert_eq!(lines[5], "state lost 5 5 2");
+        assert_eq!(lines[6], "X # # # #");
+        assert_eq!(lines[7], "* # # # #");
+        // nothing is read after quit
+        assert_eq!(lines.len(), 12);
+    }
+}
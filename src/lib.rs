@@ -0,0 +1,9 @@
+pub mod action;
+pub mod app;
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod errors;
+pub mod headless;
+pub mod logging;
+pub mod tui;
@@ -1,25 +1,93 @@
-use clap::Parser;
-use cli::Cli;
+use clap::{CommandFactory, Parser, error::ErrorKind};
 use color_eyre::Result;
-
-use crate::app::App;
-
-mod action;
-mod app;
-mod cli;
-mod components;
-mod config;
-mod errors;
-mod logging;
-mod tui;
+use dinomite_cmd::{
+    app::App,
+    cli::{Cli, Commands, ConfigCommands, check_num_dinos},
+    components::lib::topology::{Neighborhood, TopologyKind},
+    config::{Config, get_config_dir},
+    headless,
+};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    crate::errors::init()?;
-    crate::logging::init()?;
+    dinomite_cmd::errors::init()?;
+    dinomite_cmd::logging::init()?;
 
     let args = Cli::parse();
-    let mut app = App::new(args.num_dinos, args.height, args.width)?;
-    app.run().await?;
-    Ok(())
+    if let Some(Commands::Config {
+        command: ConfigCommands::Check,
+    }) = args.command
+    {
+        check_config();
+    }
+    if args.topology == TopologyKind::Hex && args.neighborhood != Neighborhood::King {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "hex boards always count the six surrounding fields, --neighborhood only applies to square boards",
+            )
+            .exit();
+    }
+    if args.headless_protocol
+        && args
+            .result_json
+            .as_ref()
+            .is_some_and(|path| path.as_os_str() == "-")
+    {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--headless-protocol answers on stdout, give --result-json a file instead of `-`",
+            )
+            .exit();
+    }
+    let config = Config::new()?;
+    let options = args.game_options(&config.game);
+    if let Err(message) = check_num_dinos(&options) {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, message)
+            .exit();
+    }
+    let result = if args.headless_protocol {
+        headless::run(options, std::io::stdin().lock(), std::io::stdout().lock())?
+    } else {
+        let display = args.display_options(&config.display);
+        let mut app = App::new(options, display, config)?;
+        let Some(result) = app.run().await? else {
+            return Ok(());
+        };
+        result
+    };
+    if let Some(path) = &args.result_json {
+        let json = serde_json::to_string_pretty(&result)?;
+        if path.as_os_str() == "-" {
+            println!("{json}");
+        } else {
+            std::fs::write(path, json + "\n")?;
+        }
+    }
+    std::process::exit(result.outcome.exit_code());
+}
+
+// Print what is wrong with the configuration and exit, unsuccessfully if anything is.
+fn check_config() -> ! {
+    let config_dir = get_config_dir();
+    let (_, report) = Config::load(&config_dir);
+    if report.files.is_empty() {
+        println!(
+            "no configuration files in {}, using the built-in defaults",
+            config_dir.display()
+        );
+    }
+    for file in &report.files {
+        println!("read {}", file.display());
+    }
+    for diagnostic in &report.diagnostics {
+        println!("{diagnostic}");
+    }
+    if report.diagnostics.is_empty() {
+        println!("no problems found");
+        std::process::exit(0);
+    }
+    std::process::exit(1);
 }
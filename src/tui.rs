@@ -40,6 +40,31 @@ pub enum Event {
     Resize(u16, u16),
 }
 
+// Ticks and frames per second unless configured otherwise.
+pub const DEFAULT_TICK_RATE: f64 = 4.0;
+pub const DEFAULT_FRAME_RATE: f64 = 60.0;
+// Highest rate accepted for either.
+pub const MAX_RATE: f64 = 1000.0;
+
+/// How often the interface is updated and redrawn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayOptions {
+    pub tick_rate: f64,
+    pub frame_rate: f64,
+    // only redraw at the frame rate if something happened since the last frame
+    pub render_on_change: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            tick_rate: DEFAULT_TICK_RATE,
+            frame_rate: DEFAULT_FRAME_RATE,
+            render_on_change: false,
+        }
+    }
+}
+
 pub struct Tui {
     pub terminal: ratatui::Terminal<Backend<Stdout>>,
     pub task: JoinHandle<()>,
@@ -61,8 +86,8 @@ impl Tui {
             cancellation_token: CancellationToken::new(),
             event_rx,
             event_tx,
-            frame_rate: 60.0,
-            tick_rate: 4.0,
+            frame_rate: DEFAULT_FRAME_RATE,
+            tick_rate: DEFAULT_TICK_RATE,
             mouse: false,
             paste: false,
         })